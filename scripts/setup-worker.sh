@@ -2,7 +2,7 @@
 #
 # Bootstraps a self-hosted nasa-rs Cloudflare Worker:
 #   - copies wrangler.toml.example -> wrangler.toml (gitignored)
-#   - creates the NASA_CACHE and RATE_LIMIT KV namespaces
+#   - creates the NASA_CACHE, RATE_LIMIT and API_KEYS KV namespaces
 #   - writes their IDs into wrangler.toml
 #   - prompts for the NASA_API_KEY secret
 #
@@ -65,6 +65,7 @@ create_namespace() {
 
 create_namespace NASA_CACHE __NASA_CACHE_ID__
 create_namespace RATE_LIMIT __RATE_LIMIT_ID__
+create_namespace API_KEYS __API_KEYS_ID__
 
 echo ""
 echo "Set your NASA API key (get a free one at https://api.nasa.gov; DEMO_KEY works for low volume):"
@@ -54,103 +54,39 @@ pub async fn execute(
                 params.insert("end_date".to_string(), end_date.clone());
             }
             
-            // Get the basic feed first
+            // The worker filters the feed and flattens it into one list
+            if sub_matches.get_flag("hazardous") {
+                params.insert("hazardous".to_string(), "true".to_string());
+            }
+            if let Some(size) = sub_matches.get_one::<String>("size") {
+                params.insert("size".to_string(), size.clone());
+            }
+            if let Some(min_dist) = sub_matches.get_one::<String>("min-distance") {
+                params.insert("min_distance".to_string(), min_dist.clone());
+            }
+            if let Some(max_dist) = sub_matches.get_one::<String>("max-distance") {
+                params.insert("max_distance".to_string(), max_dist.clone());
+            }
+            if let Some(sort) = sub_matches.get_one::<String>("sort") {
+                params.insert("sort".to_string(), sort.clone());
+            }
+            params.insert("flatten".to_string(), "true".to_string());
+            
             let data = client.get("/api/neo/feed", params).await?;
             
-            // Apply filters
-            if let Some(neo_objects) = data.get("near_earth_objects").and_then(|n| n.as_object()) {
-                let mut filtered_asteroids = Vec::new();
-                
-                for (_date, asteroids) in neo_objects {
-                    if let Some(asteroid_list) = asteroids.as_array() {
-                        for asteroid in asteroid_list {
-                            let mut include = true;
-                            
-                            // Filter by hazardous status
-                            if sub_matches.get_flag("hazardous") {
-                                if let Some(hazardous) = asteroid.get("is_potentially_hazardous_asteroid").and_then(|h| h.as_bool()) {
-                                    if !hazardous {
-                                        include = false;
-                                    }
-                                }
-                            }
-                            
-                            // Filter by size
-                            if let Some(size_filter) = sub_matches.get_one::<String>("size") {
-                                if let Some(diameter) = asteroid.get("estimated_diameter")
-                                    .and_then(|d| d.get("kilometers"))
-                                    .and_then(|k| k.get("estimated_diameter_max"))
-                                    .and_then(|v| v.as_f64()) 
-                                {
-                                    let size_match = match size_filter.as_str() {
-                                        "small" => diameter < 0.1,
-                                        "medium" => (0.1..1.0).contains(&diameter),
-                                        "large" => diameter >= 1.0,
-                                        _ => true
-                                    };
-                                    if !size_match {
-                                        include = false;
-                                    }
-                                }
-                            }
-                            
-                            // Filter by distance
-                            if let Some(min_dist) = sub_matches.get_one::<String>("min-distance") {
-                                if let Some(approaches) = asteroid.get("close_approach_data").and_then(|c| c.as_array()) {
-                                    if let Some(first_approach) = approaches.first() {
-                                        if let Some(miss_distance) = first_approach.get("miss_distance")
-                                            .and_then(|m| m.get("astronomical"))
-                                            .and_then(|a| a.as_str())
-                                            .and_then(|s| s.parse::<f64>().ok())
-                                        {
-                                            let min = min_dist.parse::<f64>().unwrap_or(0.0);
-                                            if miss_distance < min {
-                                                include = false;
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                            
-                            if let Some(max_dist) = sub_matches.get_one::<String>("max-distance") {
-                                if let Some(approaches) = asteroid.get("close_approach_data").and_then(|c| c.as_array()) {
-                                    if let Some(first_approach) = approaches.first() {
-                                        if let Some(miss_distance) = first_approach.get("miss_distance")
-                                            .and_then(|m| m.get("astronomical"))
-                                            .and_then(|a| a.as_str())
-                                            .and_then(|s| s.parse::<f64>().ok())
-                                        {
-                                            let max = max_dist.parse::<f64>().unwrap_or(f64::MAX);
-                                            if miss_distance > max {
-                                                include = false;
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                            
-                            if include {
-                                filtered_asteroids.push(asteroid.clone());
-                            }
-                        }
-                    }
-                }
-                
-                Ok(Some(json!({
-                    "element_count": filtered_asteroids.len(),
-                    "filters_applied": {
-                        "hazardous": sub_matches.get_flag("hazardous"),
-                        "size": sub_matches.get_one::<String>("size"),
-                        "distance_range": {
-                            "min": sub_matches.get_one::<String>("min-distance"),
-                            "max": sub_matches.get_one::<String>("max-distance")
-                        }
+            Ok(Some(json!({
+                "element_count": data.get("element_count"),
+                "filters_applied": {
+                    "hazardous": sub_matches.get_flag("hazardous"),
+                    "size": sub_matches.get_one::<String>("size"),
+                    "distance_range": {
+                        "min": sub_matches.get_one::<String>("min-distance"),
+                        "max": sub_matches.get_one::<String>("max-distance")
                     },
-                    "asteroids": filtered_asteroids
-                })))
-            } else {
-                Ok(Some(data))
-            }
+                    "sort": sub_matches.get_one::<String>("sort")
+                },
+                "asteroids": data.get("near_earth_objects")
+            })))
         }
         Some(("batch-lookup", sub_matches)) => {
             let ids: Vec<&str> = sub_matches.get_many::<String>("ids")
@@ -191,7 +191,7 @@ async fn explore_asteroids(client: &ApiClient, output_format: &OutputFormat) ->
                 .interact()?;
             
             let end_date: String = Input::new()
-                .with_prompt("Enter end date (YYYY-MM-DD, max 7 days)")
+                .with_prompt("Enter end date (YYYY-MM-DD, up to the server's configured limit)")
                 .validate_with(|input: &String| {
                     NaiveDate::parse_from_str(input, "%Y-%m-%d")
                         .map(|_| ())
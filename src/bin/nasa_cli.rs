@@ -184,6 +184,7 @@ fn build_cli() -> Command {
                         .arg(Arg::new("hazardous").long("hazardous").action(ArgAction::SetTrue).help("Only show potentially hazardous"))
                         .arg(Arg::new("min-distance").long("min-distance").help("Minimum approach distance (AU)"))
                         .arg(Arg::new("max-distance").long("max-distance").help("Maximum approach distance (AU)"))
+                        .arg(Arg::new("sort").long("sort").allow_hyphen_values(true).help("Sort by miss_distance|velocity|diameter|approach_time (prefix - for descending)"))
                 )
                 .subcommand(
                     Command::new("batch-lookup")
@@ -0,0 +1,93 @@
+//! Single-flight execution of identical work within an isolate.
+//!
+//! When a popular entry expires, every concurrent request for it misses at once.
+//! [`SingleFlight`] lets the first request start the work and every request that
+//! arrives while it is running await the same future instead of starting its own.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::future::Future;
+
+use futures::future::{FutureExt, LocalBoxFuture, Shared};
+
+/// In-flight futures keyed by what they compute.
+pub struct SingleFlight<T> {
+    in_flight: RefCell<HashMap<String, Shared<LocalBoxFuture<'static, T>>>>,
+}
+
+impl<T> Default for SingleFlight<T> {
+    fn default() -> Self {
+        Self { in_flight: RefCell::new(HashMap::new()) }
+    }
+}
+
+impl<T: Clone + 'static> SingleFlight<T> {
+    /// Runs `work` for `key`, or, if work for `key` is already running, waits for
+    /// it and returns a clone of its output; `work` is then dropped unpolled.
+    pub async fn run<F>(&self, key: &str, work: F) -> T
+    where
+        F: Future<Output = T> + 'static,
+    {
+        let shared = self
+            .in_flight
+            .borrow_mut()
+            .entry(key.to_string())
+            .or_insert_with(|| work.boxed_local().shared())
+            .clone();
+
+        let output = shared.clone().await;
+
+        let mut in_flight = self.in_flight.borrow_mut();
+        if in_flight.get(key).is_some_and(|current| current.ptr_eq(&shared)) {
+            in_flight.remove(key);
+        }
+        output
+    }
+
+    /// Number of keys with work in flight.
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.in_flight.borrow().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::channel::oneshot;
+    use futures::executor::block_on;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn concurrent_callers_share_one_execution() {
+        let flights = SingleFlight::<u32>::default();
+        let runs = Rc::new(Cell::new(0));
+        let (release, released) = oneshot::channel::<()>();
+
+        let first = flights.run("apod:", {
+            let runs = runs.clone();
+            async move {
+                runs.set(runs.get() + 1);
+                released.await.ok();
+                42
+            }
+        });
+        let second = flights.run("apod:", async { unreachable!("joins the first call") });
+        let (first, second, ()) = block_on(futures::future::join3(first, second, async {
+            release.send(()).unwrap();
+        }));
+
+        assert_eq!((first, second, runs.get()), (42, 42, 1));
+        assert_eq!(flights.len(), 0);
+    }
+
+    #[test]
+    fn runs_again_once_finished_and_keeps_keys_apart() {
+        let flights = SingleFlight::<&str>::default();
+
+        assert_eq!(block_on(flights.run("a", async { "first" })), "first");
+        assert_eq!(block_on(flights.run("a", async { "second" })), "second");
+        assert_eq!(block_on(flights.run("b", async { "other" })), "other");
+    }
+}
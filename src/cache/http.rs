@@ -0,0 +1,102 @@
+//! HTTP caching headers for responses served from [`super::CachedResponse`]s:
+//! validators (`ETag`, `Last-Modified`), conditional request handling and
+//! freshness headers (`Cache-Control`, `Age`).
+
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+/// Strong entity tag derived from the response body.
+pub fn etag(body: &str) -> String {
+    format!("\"{}\"", &hex::encode(Sha256::digest(body.as_bytes()))[..32])
+}
+
+/// Formats a timestamp as an HTTP date (RFC 9110 IMF-fixdate).
+pub fn http_date(time: DateTime<Utc>) -> String {
+    time.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Whether a request carrying these conditional headers can be answered with
+/// `304 Not Modified`.
+///
+/// As RFC 9110 requires, `If-Modified-Since` is ignored when `If-None-Match` is
+/// present. Entity tags are compared weakly, so `W/"x"` matches `"x"`.
+pub fn not_modified(
+    if_none_match: Option<&str>,
+    if_modified_since: Option<&str>,
+    etag: &str,
+    last_modified: DateTime<Utc>,
+) -> bool {
+    if let Some(candidates) = if_none_match {
+        let etag = etag.trim_start_matches("W/");
+        return candidates
+            .split(',')
+            .map(str::trim)
+            .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag);
+    }
+
+    if let Some(since) = if_modified_since.and_then(|value| DateTime::parse_from_rfc2822(value.trim()).ok()) {
+        // HTTP dates have whole-second precision
+        return last_modified.timestamp() <= since.timestamp();
+    }
+
+    false
+}
+
+/// `Cache-Control` value telling clients how much longer the entry stays fresh.
+pub fn cache_control(expires_at: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    format!("public, max-age={}", (expires_at - now).num_seconds().max(0))
+}
+
+/// `Age` value: seconds since the entry was fetched from the upstream.
+pub fn age(cached_at: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    (now - cached_at).num_seconds().max(0).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 + seconds, 0).unwrap()
+    }
+
+    #[test]
+    fn etag_is_stable_and_content_based() {
+        assert_eq!(etag(r#"{"a":1}"#), etag(r#"{"a":1}"#));
+        assert_ne!(etag(r#"{"a":1}"#), etag(r#"{"a":2}"#));
+        assert_eq!(etag("{}").len(), 34);
+    }
+
+    #[test]
+    fn formats_http_dates() {
+        assert_eq!(http_date(at(0)), "Tue, 14 Nov 2023 22:13:20 GMT");
+    }
+
+    #[test]
+    fn matches_if_none_match() {
+        let tag = etag("{}");
+
+        assert!(not_modified(Some(&tag), None, &tag, at(0)));
+        assert!(not_modified(Some(&format!("\"other\", W/{tag}")), None, &tag, at(0)));
+        assert!(not_modified(Some("*"), None, &tag, at(0)));
+        assert!(!not_modified(Some("\"other\""), Some(&http_date(at(10))), &tag, at(0)));
+    }
+
+    #[test]
+    fn falls_back_to_if_modified_since() {
+        let tag = etag("{}");
+        let modified = at(0) + chrono::Duration::milliseconds(400);
+
+        assert!(not_modified(None, Some(&http_date(at(0))), &tag, modified));
+        assert!(!not_modified(None, Some(&http_date(at(-1))), &tag, modified));
+        assert!(!not_modified(None, Some("yesterday"), &tag, modified));
+        assert!(!not_modified(None, None, &tag, modified));
+    }
+
+    #[test]
+    fn freshness_headers_never_go_negative() {
+        assert_eq!(cache_control(at(60), at(0)), "public, max-age=60");
+        assert_eq!(cache_control(at(60), at(90)), "public, max-age=0");
+        assert_eq!(age(at(0), at(90)), "90");
+    }
+}
@@ -1,34 +1,194 @@
+//! Response cache backed by the `NASA_CACHE` KV namespace.
+//!
+//! Entries have a soft and a hard expiry. Until `expires_at` (the endpoint's TTL)
+//! an entry is fresh; after that, until `stale_until`, it is still served but
+//! flagged stale so the caller can refresh it in the background. Beyond that the
+//! entry is kept for the stale-if-error window (`CACHE_STALE_IF_ERROR_MINUTES`
+//! past `expires_at`) and only served when the upstream fails; KV drops it once
+//! both windows have passed.
+//!
+//! Keys are namespaced by [`SCHEMA_VERSION`]; bumping it invalidates every entry
+//! at once, and the old ones expire on their own.
+//!
+//! Concurrent misses for one key are coalesced: within an isolate through
+//! [`coalesce::SingleFlight`], across isolates through a short-lived fill lock
+//! ([`CacheManager::try_lock`]).
+//!
+//! Each entry also carries its timestamps as KV metadata ([`EntryMetadata`]) so
+//! the admin API can list keys without reading every value.
+
+pub mod coalesce;
+pub mod http;
+
 use chrono::{DateTime, Utc, Duration};
 use serde::{Deserialize, Serialize};
-use worker::{kv::KvStore, Env};
+use worker::{console_error, kv::KvStore, Delay, Env};
+
+/// How many TTLs past its expiry an entry may still be served while it is refreshed.
+const STALE_WHILE_REVALIDATE_TTLS: i32 = 1;
+
+/// Version of the cached data's shape. Bump it in any release that changes what
+/// gets cached, so entries written by older releases are never served.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// How long a fill lock keeps other isolates from fetching the same key.
+const FILL_LOCK_SECONDS: i64 = 10;
+/// KV's minimum expiration; the lock's own deadline is what counts.
+const FILL_LOCK_TTL_SECONDS: u64 = 60;
+/// How long a request waits for another isolate to fill an entry, and how often
+/// it looks.
+const FILL_WAIT: std::time::Duration = std::time::Duration::from_secs(2);
+const FILL_POLL: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Stale-if-error window unless `CACHE_STALE_IF_ERROR_MINUTES` says otherwise.
+const DEFAULT_STALE_IF_ERROR_MINUTES: i64 = 24 * 60;
+/// Most keys KV returns from one list call.
+pub const KV_LIST_PAGE: usize = 1000;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CachedResponse {
     pub data: serde_json::Value,
     pub cached_at: DateTime<Utc>,
+    /// Soft expiry: the entry is fresh until then.
     pub expires_at: DateTime<Utc>,
+    /// Hard expiry: the entry may be served stale until then. Entries written
+    /// before this field existed have no stale period.
+    #[serde(default)]
+    pub stale_until: Option<DateTime<Utc>>,
+}
+
+/// Timestamps stored as KV metadata next to each entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryMetadata {
+    pub cached_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A listed cache key. Entries written before metadata was stored have none.
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheKey {
+    pub key: String,
+    #[serde(flatten)]
+    pub metadata: Option<EntryMetadata>,
+}
+
+impl From<worker::kv::Key> for CacheKey {
+    fn from(key: worker::kv::Key) -> Self {
+        Self {
+            metadata: key.metadata.and_then(|value| serde_json::from_value(value).ok()),
+            key: unversioned(&key.name).to_string(),
+        }
+    }
+}
+
+/// KV key under which `key` is stored for the current [`SCHEMA_VERSION`].
+fn versioned(key: &str) -> String {
+    format!("v{SCHEMA_VERSION}:{key}")
+}
+
+/// Fill locks live outside the versioned namespace so they never show up as entries.
+fn lock_key(key: &str) -> String {
+    format!("lock:{}", versioned(key))
+}
+
+fn unversioned(key: &str) -> &str {
+    key.strip_prefix(&versioned("")).unwrap_or(key)
+}
+
+/// Endpoint family of a cache key: its first path segment, e.g. `donki` for
+/// `donki/cme:startDate=2024-01-01`.
+pub fn family(key: &str) -> &str {
+    key.split(['/', ':']).next().unwrap_or(key)
+}
+
+/// Marker telling other isolates that an entry is being fetched.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FillLock {
+    pub until: DateTime<Utc>,
+}
+
+impl FillLock {
+    pub fn is_held(&self, now: DateTime<Utc>) -> bool {
+        now < self.until
+    }
+}
+
+/// How usable a cached entry is at a given moment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    Fresh,
+    /// Past its TTL but within the stale window: serve it and refresh.
+    Stale,
+    /// Too old to serve, except in place of an upstream error.
+    StaleIfError,
+    Expired,
+}
+
+impl CachedResponse {
+    pub fn new(data: serde_json::Value, now: DateTime<Utc>, ttl_minutes: i64) -> Self {
+        let ttl = Duration::minutes(ttl_minutes);
+        Self {
+            data,
+            cached_at: now,
+            expires_at: now + ttl,
+            stale_until: Some(now + ttl + ttl * STALE_WHILE_REVALIDATE_TTLS),
+        }
+    }
+
+    pub fn freshness(&self, now: DateTime<Utc>, stale_if_error: Duration) -> Freshness {
+        if now < self.expires_at {
+            Freshness::Fresh
+        } else if self.stale_until.is_some_and(|until| now < until) {
+            Freshness::Stale
+        } else if now < self.expires_at + stale_if_error {
+            Freshness::StaleIfError
+        } else {
+            Freshness::Expired
+        }
+    }
+
+    /// When the entry becomes useless and can be dropped from KV.
+    fn retain_until(&self, stale_if_error: Duration) -> DateTime<Utc> {
+        self.stale_until
+            .unwrap_or(self.expires_at)
+            .max(self.expires_at + stale_if_error)
+    }
 }
 
 pub struct CacheManager {
     kv: KvStore,
+    stale_if_error: Duration,
 }
 
 impl CacheManager {
     pub fn new(env: &Env) -> worker::Result<Self> {
         let kv = env.kv("NASA_CACHE")?;
+        let stale_if_error = env
+            .var("CACHE_STALE_IF_ERROR_MINUTES")
+            .ok()
+            .and_then(|v| v.to_string().trim().parse::<i64>().ok())
+            .filter(|minutes| *minutes >= 0)
+            .unwrap_or(DEFAULT_STALE_IF_ERROR_MINUTES);
         
-        Ok(Self { kv })
+        Ok(Self { kv, stale_if_error: Duration::minutes(stale_if_error) })
+    }
+    
+    /// How usable `cached` is right now.
+    pub fn freshness(&self, cached: &CachedResponse) -> Freshness {
+        cached.freshness(Utc::now(), self.stale_if_error)
     }
     
+    /// Returns the entry for `key` unless it is too old to serve at all; check
+    /// [`CacheManager::freshness`] to tell how it may be used.
     pub async fn get(&self, key: &str) -> worker::Result<Option<CachedResponse>> {
-        match self.kv.get(key).json::<CachedResponse>().await {
+        match self.kv.get(&versioned(key)).json::<CachedResponse>().await {
             Ok(Some(cached)) => {
                 // Check if cache is expired
-                if cached.expires_at > Utc::now() {
+                if self.freshness(&cached) != Freshness::Expired {
                     Ok(Some(cached))
                 } else {
                     // Delete expired cache
-                    let _ = self.kv.delete(key).await;
+                    let _ = self.kv.delete(&versioned(key)).await;
                     Ok(None)
                 }
             }
@@ -37,34 +197,116 @@ impl CacheManager {
         }
     }
     
-    pub async fn set(&self, key: &str, data: serde_json::Value, ttl_minutes: i64) -> worker::Result<()> {
+    /// Stores `data` under `key` and returns the entry as written.
+    pub async fn set(&self, key: &str, data: serde_json::Value, ttl_minutes: i64) -> worker::Result<CachedResponse> {
         let now = Utc::now();
-        let cached_response = CachedResponse {
-            data,
-            cached_at: now,
-            expires_at: now + Duration::minutes(ttl_minutes),
-        };
+        let cached_response = CachedResponse::new(data, now, ttl_minutes);
+        let retention = cached_response.retain_until(self.stale_if_error) - now;
         
         self.kv
-            .put(key, serde_json::to_string(&cached_response).map_err(|e| worker::Error::RustError(e.to_string()))?)
+            .put(&versioned(key), serde_json::to_string(&cached_response).map_err(|e| worker::Error::RustError(e.to_string()))?)
+            ?
+            .metadata(EntryMetadata { cached_at: cached_response.cached_at, expires_at: cached_response.expires_at })
             ?
-            .expiration_ttl(ttl_minutes as u64 * 60)
+            .expiration_ttl(retention.num_seconds() as u64)
             .execute()
             .await
             ?;
         
-        Ok(())
+        Ok(cached_response)
     }
     
-    #[allow(dead_code)]
     pub async fn delete(&self, key: &str) -> worker::Result<()> {
         self.kv
-            .delete(key)
+            .delete(&versioned(key))
             .await
             ?;
         
         Ok(())
     }
+
+    /// Marks `key` as being filled by this request. Returns `false` when another
+    /// request already holds an unexpired lock.
+    ///
+    /// KV has no compare-and-swap, so two isolates occasionally both get the lock;
+    /// it only has to stop most of a stampede. KV failures count as acquired.
+    pub async fn try_lock(&self, key: &str) -> bool {
+        let now = Utc::now();
+        match self.kv.get(&lock_key(key)).json::<FillLock>().await {
+            Ok(Some(lock)) if lock.is_held(now) => return false,
+            Ok(_) => {}
+            Err(e) => console_error!("Failed to read fill lock for {}: {}", key, e),
+        }
+
+        let lock = FillLock { until: now + Duration::seconds(FILL_LOCK_SECONDS) };
+        let result = match self.kv.put(&lock_key(key), serde_json::to_string(&lock).unwrap_or_default()) {
+            Ok(put) => put.expiration_ttl(FILL_LOCK_TTL_SECONDS).execute().await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            console_error!("Failed to write fill lock for {}: {}", key, e);
+        }
+        true
+    }
+
+    pub async fn unlock(&self, key: &str) {
+        if let Err(e) = self.kv.delete(&lock_key(key)).await {
+            console_error!("Failed to release fill lock for {}: {}", key, e);
+        }
+    }
+
+    /// Waits up to [`FILL_WAIT`] for another request to store a fresh entry for `key`.
+    pub async fn wait_for_fill(&self, key: &str) -> worker::Result<Option<CachedResponse>> {
+        let mut waited = std::time::Duration::ZERO;
+        while waited < FILL_WAIT {
+            Delay::from(FILL_POLL).await;
+            waited += FILL_POLL;
+
+            if let Some(cached) = self.get(key).await? {
+                if self.freshness(&cached) == Freshness::Fresh {
+                    return Ok(Some(cached));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Keys of the current schema version starting with `prefix`, in key order,
+    /// stopping after `limit` keys.
+    /// The flag tells whether every matching key was returned.
+    pub async fn keys(&self, prefix: &str, limit: Option<usize>) -> worker::Result<(Vec<CacheKey>, bool)> {
+        let mut keys = Vec::new();
+        let mut cursor = None;
+
+        loop {
+            let page_limit = limit.map_or(KV_LIST_PAGE, |limit| (limit - keys.len()).min(KV_LIST_PAGE));
+            let (page, next) = self.keys_page(prefix, cursor, page_limit).await?;
+            keys.extend(page);
+
+            match next {
+                Some(next) if limit.is_none_or(|limit| keys.len() < limit) => cursor = Some(next),
+                next => return Ok((keys, next.is_none())),
+            }
+        }
+    }
+
+    /// One page of at most `limit` (up to 1000) keys starting with `prefix`,
+    /// resuming from a `cursor` returned with the previous page. The returned
+    /// cursor is `None` once every matching key has been listed.
+    pub async fn keys_page(
+        &self,
+        prefix: &str,
+        cursor: Option<String>,
+        limit: usize,
+    ) -> worker::Result<(Vec<CacheKey>, Option<String>)> {
+        let mut list = self.kv.list().prefix(versioned(prefix)).limit(limit.min(KV_LIST_PAGE) as u64);
+        if let Some(cursor) = cursor {
+            list = list.cursor(cursor);
+        }
+        let page = list.execute().await?;
+        let next = page.cursor.filter(|_| !page.list_complete);
+        Ok((page.keys.into_iter().map(CacheKey::from).collect(), next))
+    }
 }
 
 pub fn get_cache_key(endpoint: &str, params: &[(String, String)]) -> String {
@@ -80,25 +322,82 @@ pub fn get_cache_key(endpoint: &str, params: &[(String, String)]) -> String {
     format!("{endpoint}:{param_string}")
 }
 
-pub fn get_ttl_for_endpoint(endpoint: &str) -> i64 {
-    match endpoint {
-        // APOD updates daily
-        "apod" => 1440, // 24 hours
-        
-        // Near real-time data
-        "neo/feed" => 60, // 1 hour
-        "donki/cme" => 30, // 30 minutes
-        "donki/flr" => 30, // 30 minutes
-        "donki/notifications" => 15, // 15 minutes
-        
-        // Static or slowly changing data
-        "neo/browse" => 360, // 6 hours
-        "mars-photos/manifests" => 1440, // 24 hours
-        "techtransfer" => 10080, // 1 week
-        "media/search" => 360, // 6 hours
-        "exoplanets/query" => 1440, // 24 hours
-        
-        // Default
-        _ => 60, // 1 hour
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000, 0).unwrap() + Duration::minutes(minutes)
+    }
+
+    #[test]
+    fn fill_locks_expire() {
+        let lock = FillLock { until: at(1) };
+
+        assert!(lock.is_held(at(0)));
+        assert!(!lock.is_held(at(1)));
+        assert_eq!(lock_key("apod:"), format!("lock:{}", versioned("apod:")));
+    }
+
+    #[test]
+    fn groups_keys_by_endpoint_family() {
+        assert_eq!(family("donki/cme:startDate=2024-01-01"), "donki");
+        assert_eq!(family("mars-photos/curiosity/photos:sol=1000"), "mars-photos");
+        assert_eq!(family("apod:date=2024-01-01"), "apod");
+        assert_eq!(family("apod"), "apod");
+    }
+
+    #[test]
+    fn reads_listed_key_metadata() {
+        let key: worker::kv::Key = serde_json::from_value(serde_json::json!({
+            "name": versioned("apod:"),
+            "metadata": {"cached_at": at(0), "expires_at": at(60)},
+        }))
+        .unwrap();
+        let legacy: worker::kv::Key = serde_json::from_value(serde_json::json!({"name": versioned("apod:count=2")})).unwrap();
+
+        let key = CacheKey::from(key);
+        let legacy = CacheKey::from(legacy);
+
+        assert_eq!(key.metadata, Some(EntryMetadata { cached_at: at(0), expires_at: at(60) }));
+        assert_eq!(legacy.metadata, None);
+        assert_eq!(legacy.key, "apod:count=2");
+    }
+
+    const NO_ERROR_WINDOW: Duration = Duration::zero();
+
+    #[test]
+    fn entries_go_stale_after_ttl_and_expire_after_the_stale_window() {
+        let cached = CachedResponse::new(serde_json::json!({}), at(0), 60);
+
+        assert_eq!(cached.freshness(at(59), NO_ERROR_WINDOW), Freshness::Fresh);
+        assert_eq!(cached.freshness(at(60), NO_ERROR_WINDOW), Freshness::Stale);
+        assert_eq!(cached.freshness(at(119), NO_ERROR_WINDOW), Freshness::Stale);
+        assert_eq!(cached.freshness(at(120), NO_ERROR_WINDOW), Freshness::Expired);
+    }
+
+    #[test]
+    fn stale_if_error_window_extends_past_the_stale_window() {
+        let cached = CachedResponse::new(serde_json::json!({}), at(0), 60);
+        let window = Duration::minutes(24 * 60);
+
+        assert_eq!(cached.freshness(at(90), window), Freshness::Stale);
+        assert_eq!(cached.freshness(at(120), window), Freshness::StaleIfError);
+        assert_eq!(cached.freshness(at(60 + 24 * 60), window), Freshness::Expired);
+        assert_eq!(cached.retain_until(window), at(60 + 24 * 60));
+        assert_eq!(cached.retain_until(NO_ERROR_WINDOW), at(120));
+    }
+
+    #[test]
+    fn legacy_entries_have_no_stale_window() {
+        let cached: CachedResponse = serde_json::from_value(serde_json::json!({
+            "data": {},
+            "cached_at": at(0),
+            "expires_at": at(60),
+        }))
+        .unwrap();
+
+        assert_eq!(cached.freshness(at(30), NO_ERROR_WINDOW), Freshness::Fresh);
+        assert_eq!(cached.freshness(at(61), NO_ERROR_WINDOW), Freshness::Expired);
     }
 }
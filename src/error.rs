@@ -3,6 +3,7 @@
 //! This module provides a comprehensive error type system that unifies
 //! different error sources and provides consistent error responses.
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use worker::{Error as WorkerError, Response};
 
@@ -10,21 +11,50 @@ use worker::{Error as WorkerError, Response};
 pub type Result<T> = std::result::Result<T, NasaApiError>;
 
 /// Standardized error response structure returned to API clients.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ErrorResponse {
     /// The error type/category.
     pub error: String,
+    /// Stable, machine-readable error code, e.g. `rate_limited`.
+    pub code: String,
+    /// Label of the upstream API that failed, for gateway errors.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upstream: Option<String>,
     /// Human-readable error message.
     pub message: String,
     /// HTTP status code.
     pub status_code: u16,
+    /// Every invalid parameter, for validation errors.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub problems: Vec<Problem>,
+}
+
+/// One invalid request parameter.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Problem {
+    /// Name of the parameter.
+    pub parameter: String,
+    /// What is wrong with it, e.g. `must be a date in YYYY-MM-DD format`.
+    pub message: String,
+}
+
+impl Problem {
+    pub fn new(parameter: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { parameter: parameter.into(), message: message.into() }
+    }
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.parameter, self.message)
+    }
 }
 
 /// Main error type for the NASA API proxy.
 /// 
 /// This enum represents all possible errors that can occur within the application,
 /// providing a unified error handling approach.
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, Clone, thiserror::Error)]
 pub enum NasaApiError {
     /// Cloudflare Worker-specific errors.
     #[error("Worker error: {0}")]
@@ -34,18 +64,34 @@ pub enum NasaApiError {
     #[error("Request error: {0}")]
     Request(String),
     
-    /// Errors returned by NASA APIs.
-    #[error("NASA API error: {0}")]
-    NasaApi(String),
+    /// An upstream API did not answer in time.
+    #[error("{upstream} did not respond within {seconds}s")]
+    UpstreamTimeout { upstream: &'static str, seconds: u64 },
+    
+    /// An upstream API could not be reached or failed on its side.
+    #[error("{upstream} is unavailable: {detail}")]
+    UpstreamUnavailable { upstream: &'static str, detail: String },
+    
+    /// An upstream API refused the parameters it was sent.
+    #[error("{upstream} rejected the request: {detail}")]
+    UpstreamRejected { upstream: &'static str, detail: String },
+    
+    /// The worker's key for an upstream API is out of quota.
+    #[error("{upstream} quota exhausted: {detail}")]
+    UpstreamQuotaExhausted { upstream: &'static str, detail: String },
     
-    /// Input validation errors.
-    #[error("Validation error: {0}")]
-    Validation(String),
+    /// Request parameters that failed validation, reported all at once.
+    #[error("Invalid parameters: {}", .0.iter().map(Problem::to_string).collect::<Vec<_>>().join("; "))]
+    Validation(Vec<Problem>),
     
     /// Authentication/authorization errors.
     #[error("Authentication error: {0}")]
     Authentication(String),
     
+    /// Valid credentials that are not allowed to access the resource.
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+    
     /// Rate limiting errors.
     #[error("Rate limit exceeded")]
     RateLimit,
@@ -62,6 +108,10 @@ pub enum NasaApiError {
     #[error("Not found: {0}")]
     NotFound(String),
     
+    /// The route exists but not for this HTTP method.
+    #[error("Method not allowed: {0}")]
+    MethodNotAllowed(String),
+    
     /// Bad request errors.
     #[error("Bad request: {0}")]
     BadRequest(String),
@@ -75,20 +125,64 @@ impl NasaApiError {
     /// Returns the appropriate HTTP status code for this error.
     pub fn status_code(&self) -> u16 {
         match self {
-            NasaApiError::Validation(_) | NasaApiError::BadRequest(_) => 400,
+            NasaApiError::Validation(_) | NasaApiError::BadRequest(_) | NasaApiError::UpstreamRejected { .. } => 400,
             NasaApiError::Authentication(_) => 401,
+            NasaApiError::Forbidden(_) => 403,
             NasaApiError::NotFound(_) => 404,
+            NasaApiError::MethodNotAllowed(_) => 405,
             NasaApiError::RateLimit => 429,
+            NasaApiError::Request(_) | NasaApiError::UpstreamUnavailable { .. } => 502,
+            NasaApiError::UpstreamQuotaExhausted { .. } => 503,
+            NasaApiError::UpstreamTimeout { .. } => 504,
             _ => 500,
         }
     }
     
+    /// Machine-readable code identifying the kind of error, stable across releases.
+    pub fn code(&self) -> &'static str {
+        match self {
+            NasaApiError::Worker(_) => "worker_error",
+            NasaApiError::Request(_) => "upstream_unreachable",
+            NasaApiError::UpstreamTimeout { .. } => "upstream_timeout",
+            NasaApiError::UpstreamUnavailable { .. } => "upstream_unavailable",
+            NasaApiError::UpstreamRejected { .. } => "upstream_rejected",
+            NasaApiError::UpstreamQuotaExhausted { .. } => "upstream_quota_exhausted",
+            NasaApiError::Validation(_) => "validation_failed",
+            NasaApiError::Authentication(_) => "unauthorized",
+            NasaApiError::Forbidden(_) => "forbidden",
+            NasaApiError::RateLimit => "rate_limited",
+            NasaApiError::Cache(_) => "cache_error",
+            NasaApiError::Serialization(_) => "serialization_error",
+            NasaApiError::NotFound(_) => "not_found",
+            NasaApiError::MethodNotAllowed(_) => "method_not_allowed",
+            NasaApiError::BadRequest(_) => "bad_request",
+            NasaApiError::Internal(_) => "internal_error",
+        }
+    }
+    
+    /// Upstream API responsible for a gateway error.
+    pub fn upstream(&self) -> Option<&'static str> {
+        match self {
+            NasaApiError::UpstreamTimeout { upstream, .. }
+            | NasaApiError::UpstreamUnavailable { upstream, .. }
+            | NasaApiError::UpstreamRejected { upstream, .. }
+            | NasaApiError::UpstreamQuotaExhausted { upstream, .. } => Some(upstream),
+            _ => None,
+        }
+    }
+    
     /// Converts the error into an HTTP Response with appropriate status code and JSON body.
     pub fn to_response(&self) -> Response {
         let error_response = ErrorResponse {
-            error: format!("{self:?}").split("(").next().unwrap_or("Unknown").to_string(),
+            error: format!("{self:?}").split(['(', ' ']).next().unwrap_or("Unknown").to_string(),
+            code: self.code().to_string(),
+            upstream: self.upstream().map(str::to_string),
             message: self.to_string(),
             status_code: self.status_code(),
+            problems: match self {
+                NasaApiError::Validation(problems) => problems.clone(),
+                _ => Vec::new(),
+            },
         };
         
         Response::from_json(&error_response)
@@ -125,4 +219,42 @@ impl From<NasaApiError> for worker::Error {
     fn from(err: NasaApiError) -> Self {
         worker::Error::RustError(err.to_string())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_errors_to_statuses_and_codes() {
+        let cases = [
+            (NasaApiError::BadRequest("x".into()), 400, "bad_request"),
+            (NasaApiError::Validation(vec![Problem::new("x", "is required")]), 400, "validation_failed"),
+            (NasaApiError::NotFound("x".into()), 404, "not_found"),
+            (NasaApiError::RateLimit, 429, "rate_limited"),
+            (NasaApiError::UpstreamRejected { upstream: "x", detail: "x".into() }, 400, "upstream_rejected"),
+            (NasaApiError::UpstreamUnavailable { upstream: "x", detail: "x".into() }, 502, "upstream_unavailable"),
+            (NasaApiError::UpstreamQuotaExhausted { upstream: "x", detail: "x".into() }, 503, "upstream_quota_exhausted"),
+            (NasaApiError::UpstreamTimeout { upstream: "x", seconds: 15 }, 504, "upstream_timeout"),
+            (NasaApiError::Internal("x".into()), 500, "internal_error"),
+        ];
+
+        for (error, status, code) in cases {
+            assert_eq!(error.status_code(), status, "{error:?}");
+            assert_eq!(error.code(), code, "{error:?}");
+        }
+    }
+
+    #[test]
+    fn lists_every_problem_in_the_message() {
+        let error = NasaApiError::Validation(vec![
+            Problem::new("start_date", "must be a date in YYYY-MM-DD format"),
+            Problem::new("utm_source", "is not a parameter of this endpoint"),
+        ]);
+
+        assert_eq!(
+            error.to_string(),
+            "Invalid parameters: start_date must be a date in YYYY-MM-DD format; utm_source is not a parameter of this endpoint"
+        );
+    }
+}
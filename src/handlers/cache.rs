@@ -0,0 +1,109 @@
+//! Admin endpoints for inspecting and purging the `NASA_CACHE` namespace. Every
+//! route requires the admin token.
+
+use std::collections::BTreeMap;
+
+use serde_json::json;
+use worker::{Request, Response, RouteContext};
+
+use crate::cache::{self, CacheManager};
+use crate::error::{NasaApiError, Result};
+use crate::middleware::auth;
+use crate::utils;
+use super::HandlerContext;
+
+/// Names the entry purged by `DELETE /api/admin/cache`. Not `key`, which is
+/// [`auth::API_KEY_PARAM`] and would be taken for a client key.
+const CACHE_KEY_PARAM: &str = "cache_key";
+/// Keys listed when no `limit` is given.
+const DEFAULT_LIST_LIMIT: usize = 100;
+/// Most keys listed in one response.
+const MAX_LIST_LIMIT: usize = 1000;
+/// Most keys counted by one stats call; KV lists are one subrequest per 1000
+/// keys, so larger caches are counted over several calls.
+const MAX_STATS_KEYS: usize = 10_000;
+/// Most keys deleted by one prefix purge; KV deletes are one subrequest each, so
+/// larger purges are done over several calls.
+const MAX_PURGE: usize = 500;
+
+/// `GET /api/admin/cache` — keys starting with `prefix` (default all) with their
+/// `cached_at`/`expires_at`, up to `limit`.
+pub async fn list_keys(req: Request, ctx: RouteContext<HandlerContext>) -> Result<Response> {
+    let (env, _, _) = &ctx.data;
+    auth::require_admin(&req, env)?;
+
+    let prefix = utils::query_param(&req, "prefix")?.unwrap_or_default();
+    let limit = match utils::query_param(&req, "limit")? {
+        Some(limit) => limit
+            .parse::<usize>()
+            .ok()
+            .filter(|limit| (1..=MAX_LIST_LIMIT).contains(limit))
+            .ok_or_else(|| NasaApiError::BadRequest(format!("'limit' must be between 1 and {MAX_LIST_LIMIT}")))?,
+        None => DEFAULT_LIST_LIMIT,
+    };
+
+    let (keys, complete) = CacheManager::new(env)?.keys(&prefix, Some(limit)).await?;
+
+    Ok(Response::from_json(&json!({
+        "prefix": prefix,
+        "keys": keys,
+        "complete": complete,
+    }))?)
+}
+
+/// `GET /api/admin/cache/stats` — number of cached keys per endpoint family,
+/// counting at most [`MAX_STATS_KEYS`] keys per call. When `complete` is false,
+/// the counts are partial and calling again with the returned `cursor` counts
+/// the next keys.
+pub async fn stats(req: Request, ctx: RouteContext<HandlerContext>) -> Result<Response> {
+    let (env, _, _) = &ctx.data;
+    auth::require_admin(&req, env)?;
+
+    let cache_manager = CacheManager::new(env)?;
+    let mut cursor = utils::query_param(&req, "cursor")?.filter(|cursor| !cursor.is_empty());
+    let mut total = 0;
+    let mut families = BTreeMap::<String, u64>::new();
+    loop {
+        let (keys, next) = cache_manager.keys_page("", cursor, MAX_STATS_KEYS - total).await?;
+        total += keys.len();
+        for key in &keys {
+            *families.entry(cache::family(&key.key).to_string()).or_default() += 1;
+        }
+        cursor = next;
+        if cursor.is_none() || total >= MAX_STATS_KEYS {
+            break;
+        }
+    }
+
+    Ok(Response::from_json(&json!({
+        "total": total,
+        "families": families,
+        "complete": cursor.is_none(),
+        "cursor": cursor,
+    }))?)
+}
+
+/// `DELETE /api/admin/cache` — purges the entry named by `cache_key`, or up to
+/// [`MAX_PURGE`] entries starting with `prefix`. `complete` is false when a
+/// prefix purge has to be repeated to remove the rest.
+pub async fn purge(req: Request, ctx: RouteContext<HandlerContext>) -> Result<Response> {
+    let (env, _, _) = &ctx.data;
+    auth::require_admin(&req, env)?;
+
+    let cache_manager = CacheManager::new(env)?;
+
+    match (utils::query_param(&req, CACHE_KEY_PARAM)?, utils::query_param(&req, "prefix")?) {
+        (Some(key), None) => {
+            cache_manager.delete(&key).await?;
+            Ok(Response::from_json(&json!({ "deleted": 1, "complete": true }))?)
+        }
+        (None, Some(prefix)) if !prefix.is_empty() => {
+            let (keys, complete) = cache_manager.keys(&prefix, Some(MAX_PURGE)).await?;
+            for key in &keys {
+                cache_manager.delete(&key.key).await?;
+            }
+            Ok(Response::from_json(&json!({ "deleted": keys.len(), "complete": complete }))?)
+        }
+        _ => Err(NasaApiError::BadRequest(format!("Exactly one of '{CACHE_KEY_PARAM}' or a non-empty 'prefix' is required"))),
+    }
+}
@@ -1,7 +1,7 @@
 use worker::*;
-use serde_yaml;
 
-const OPENAPI_SPEC: &str = include_str!("../../openapi.yaml");
+use crate::openapi;
+
 const SWAGGER_UI_HTML: &str = r#"<!DOCTYPE html>
 <html lang="en">
 <head>
@@ -434,15 +434,8 @@ const SWAGGER_UI_HTML: &str = r#"<!DOCTYPE html>
 </body>
 </html>"#;
 
-pub async fn get_openapi_json(_req: Request, _data: RouteContext<(Env, Context)>) -> worker::Result<Response> {
-    // Parse YAML and convert to JSON
-    let yaml_value: serde_yaml::Value = serde_yaml::from_str(OPENAPI_SPEC)
-        .map_err(|e| worker::Error::RustError(format!("Failed to parse OpenAPI spec: {e}")))?;
-    
-    let json_string = serde_json::to_string_pretty(&yaml_value)
-        .map_err(|e| worker::Error::RustError(format!("Failed to convert to JSON: {e}")))?;
-    
-    Response::ok(json_string)
+pub async fn get_openapi_json(_req: Request, _data: RouteContext<super::HandlerContext>) -> worker::Result<Response> {
+    Response::ok(openapi::json())
         .map(|r| {
             r.with_headers(Headers::from_iter(vec![
                 ("Content-Type", "application/json"),
@@ -451,8 +444,8 @@ pub async fn get_openapi_json(_req: Request, _data: RouteContext<(Env, Context)>
         })
 }
 
-pub async fn get_openapi_yaml(_req: Request, _data: RouteContext<(Env, Context)>) -> worker::Result<Response> {
-    Response::ok(OPENAPI_SPEC)
+pub async fn get_openapi_yaml(_req: Request, _data: RouteContext<super::HandlerContext>) -> worker::Result<Response> {
+    Response::ok(openapi::yaml())
         .map(|r| {
             r.with_headers(Headers::from_iter(vec![
                 ("Content-Type", "application/x-yaml"),
@@ -461,7 +454,7 @@ pub async fn get_openapi_yaml(_req: Request, _data: RouteContext<(Env, Context)>
         })
 }
 
-pub async fn get_swagger_ui(_req: Request, _data: RouteContext<(Env, Context)>) -> worker::Result<Response> {
+pub async fn get_swagger_ui(_req: Request, _data: RouteContext<super::HandlerContext>) -> worker::Result<Response> {
     Response::ok(SWAGGER_UI_HTML)
         .map(|r| {
             r.with_headers(Headers::from_iter(vec![
@@ -1,80 +1,27 @@
 //! Request handlers for various NASA API endpoints.
 //! 
-//! This module contains all the handler functions that process incoming requests
-//! and interact with NASA's various APIs.
+//! Proxied NASA endpoints are declared in [`crate::registry`] and served by the
+//! generic [`proxy`] pipeline; the remaining handlers serve the worker's own pages.
 
-/// Astronomy Picture of the Day (APOD) handler.
-pub mod apod;
+/// Admin cache inspection and purging.
+pub mod cache;
 /// API documentation handlers.
 pub mod docs;
-/// Space Weather Database (DONKI) handlers.
-pub mod donki;
-/// Earth imagery and assets handlers.
-pub mod earth;
-/// Earth Polychromatic Imaging Camera (EPIC) handlers.
-pub mod epic;
-/// Exoplanet archive query handlers.
-pub mod exoplanets;
-/// Mars rover photos handlers.
-pub mod mars;
-/// NASA Image and Video Library handlers.
-pub mod media;
-/// Near Earth Objects (NEO) handlers.
-pub mod neo;
-/// Solar System Dynamics (SSD/CNEOS) handlers.
-pub mod ssd;
-/// Technology Transfer handlers.
-pub mod tech;
+/// The worker's own pages.
+pub mod pages;
+/// Generic pipeline serving the endpoints declared in the registry.
+pub mod proxy;
+/// Per-client usage reports.
+pub mod usage;
 
 // Common handler utilities
-use worker::{Response, RouteContext, Env, Context};
-use crate::utils;
+use std::rc::Rc;
+use worker::{Env, Context};
+
+use crate::middleware::client::Client;
 
 /// Type alias for the context passed to all handler functions.
 /// Contains the Worker environment and context needed for processing requests.
-pub type HandlerContext = (Env, Context);
-
-/// Makes an authenticated request to a NASA API endpoint.
-/// 
-/// This function handles adding the API key to the request URL and processing
-/// the response, including error handling for non-successful status codes.
-/// 
-/// # Arguments
-/// 
-/// * `url` - The NASA API endpoint URL (without API key)
-/// * `ctx` - The route context containing environment variables
-/// 
-/// # Returns
-/// 
-/// A Worker Response containing the API response body or an error
-pub async fn make_nasa_request(
-    url: &str,
-    ctx: &RouteContext<HandlerContext>,
-) -> worker::Result<Response> {
-    let (env, _) = &ctx.data;
-    let api_key = utils::get_api_key(env)?;
-    
-    let full_url = if url.contains('?') {
-        format!("{url}&api_key={api_key}")
-    } else {
-        format!("{url}?api_key={api_key}")
-    };
-    
-    let response = reqwest::get(&full_url)
-        .await
-        .map_err(|e| worker::Error::RustError(format!("Request failed: {e}")))?;
-    
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_default();
-        return worker::Response::error(
-            format!("NASA API returned {status} - {error_text}"),
-            status.as_u16()
-        );
-    }
-    
-    let body = response.text().await
-        .map_err(|e| worker::Error::RustError(format!("Failed to read response: {e}")))?;
-    
-    Response::ok(body)
-}
\ No newline at end of file
+/// The context is shared with `main`, which schedules work after the response.
+/// The client is the one `main` authenticated, for requests under `/api/`.
+pub type HandlerContext = (Env, Rc<Context>, Option<Client>);
@@ -0,0 +1,10 @@
+//! The worker's own HTML pages.
+
+use worker::{Headers, Response, Result};
+
+/// `GET /` — landing page linking to the API documentation.
+pub fn landing() -> Result<Response> {
+    Response::ok(include_str!("landing.html")).map(|r| {
+        r.with_headers(Headers::from_iter(vec![("Content-Type", "text/html; charset=utf-8")]))
+    })
+}
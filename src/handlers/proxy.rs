@@ -0,0 +1,510 @@
+//! Generic pipeline serving every endpoint declared in [`crate::registry`].
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use worker::{console_error, Context, Env, Request, Response, RouteContext};
+
+use chrono::Utc;
+use futures::future::join_all;
+
+use crate::cache::coalesce::SingleFlight;
+use crate::cache::{http, CacheManager, CachedResponse, Freshness};
+use crate::error::{NasaApiError, Problem, Result};
+use crate::models::ApiResponse;
+use crate::registry::{Endpoint, Format, ProxyRequest, Split, Version, FIELDS_PARAM};
+use crate::upstream::{Upstream, UpstreamClient};
+use crate::utils;
+use super::HandlerContext;
+
+/// Serves one request for `endpoint`: validate, check the cache, fetch upstream on
+/// a miss, store the result and tag the response with `X-Cache-Status`.
+///
+/// Stale entries are served as-is (`STALE`) and refreshed after the response has
+/// been sent. When the upstream fails, an entry still inside the stale-if-error
+/// window is served instead (`STALE-ERROR`) with a `Warning` header.
+///
+/// Concurrent misses for the same key share one upstream fetch (see [`fill`]).
+///
+/// Cached responses carry `ETag`/`Last-Modified` validators, and conditional
+/// requests that still match are answered with `304 Not Modified`.
+///
+/// The cache holds the upstream payload, shared by both [`Version`]s;
+/// normalizing and the v2 envelope are applied on the way out (see [`render`]).
+///
+/// Requests an endpoint's [`Split`] breaks into chunks look up (and fill) each
+/// chunk's entry concurrently and are served from the merged entry.
+pub async fn serve(
+    endpoint: &'static Endpoint,
+    version: Version,
+    req: Request,
+    ctx: RouteContext<HandlerContext>,
+) -> Result<Response> {
+    let (env, worker_ctx, _) = &ctx.data;
+
+    let path: HashMap<String, String> = endpoint
+        .route_params()
+        .filter_map(|name| ctx.param(name).map(|value| (name.to_string(), value.clone())))
+        .collect();
+    let request = ProxyRequest::new(endpoint, path, utils::parse_query_params(&req)?);
+    endpoint.check(&request, span_limit(endpoint, env))?;
+
+    if endpoint.format == Format::Binary {
+        let client = UpstreamClient::from_env(env);
+        let upstream = client.get(endpoint.source, &endpoint.upstream_path(&request), &endpoint.upstream_query(&request)).await?;
+        let content_type = upstream.header("content-type").unwrap_or("application/octet-stream").to_string();
+        let mut response = Response::from_bytes(upstream.body)?;
+        response.headers_mut().set("Content-Type", &content_type)?;
+        return Ok(response);
+    }
+
+    let chunks = chunks(endpoint, &request);
+    let served = match endpoint.split {
+        Some(split) if chunks.len() > 1 => {
+            let served = join_all(chunks.into_iter().map(|chunk| lookup(endpoint, env, worker_ctx, chunk, false))).await;
+            combine(&split, served.into_iter().collect::<Result<_>>()?)
+        }
+        _ => lookup(endpoint, env, worker_ctx, request.clone(), true).await?,
+    };
+
+    let payload = render(endpoint, version, &request, &served.entry.data)?;
+    let mut response = respond(&req, &served.entry, &payload, version, served.status)?;
+    if let Some(code) = served.failure {
+        response.headers_mut().set("Warning", &format!("111 - \"Revalidation Failed: {code}\""))?;
+    }
+    Ok(response)
+}
+
+/// A cache entry a response is built from.
+struct Served {
+    entry: CachedResponse,
+    /// `X-Cache-Status` of the response.
+    status: &'static str,
+    /// Code of the upstream error a `STALE-ERROR` entry stands in for.
+    failure: Option<&'static str>,
+}
+
+/// `X-Cache-Status` values from the one that tells the most about a response
+/// to the least; a response built from several entries reports the first that
+/// applies to any of them.
+const STATUSES: &[&str] = &["MISS", "STALE-ERROR", "STALE", "HIT"];
+
+/// The configured span limit of an endpoint with a [`Split`], read from its
+/// environment variable; values over the split's ceiling are clamped.
+pub fn span_limit(endpoint: &Endpoint, env: &Env) -> Option<i64> {
+    let split = endpoint.split?;
+    let configured = env.var(split.limit_var).ok().map(|v| v.to_string());
+    let (limit, warning) = split.limit(configured.as_deref());
+    if let Some(warning) = warning {
+        console_error!("{}", warning);
+    }
+    Some(limit)
+}
+
+/// The requests to fetch for a checked `request`: the request itself, or the
+/// chunks of an endpoint with a [`Split`].
+fn chunks(endpoint: &Endpoint, request: &ProxyRequest) -> Vec<ProxyRequest> {
+    match endpoint.split {
+        Some(split) => (split.chunks)(request),
+        None => vec![request.clone()],
+    }
+}
+
+/// Merges the entries of a request's chunks into one: the payloads as `split`
+/// merges them, validators from the newest entry, and a freshness that ends
+/// with the first entry to expire.
+fn combine(split: &Split, parts: Vec<Served>) -> Served {
+    let status = STATUSES
+        .iter()
+        .copied()
+        .find(|status| parts.iter().any(|part| part.status == *status))
+        .unwrap_or("HIT");
+    let failure = parts.iter().find_map(|part| part.failure);
+    let cached_at = parts.iter().map(|part| part.entry.cached_at).max().unwrap_or_else(Utc::now);
+    let expires_at = parts.iter().map(|part| part.entry.expires_at).min().unwrap_or(cached_at);
+    let stale_until = parts.iter().filter_map(|part| part.entry.stale_until).min();
+
+    let data = (split.merge)(parts.into_iter().map(|part| part.entry.data).collect());
+    Served { entry: CachedResponse { data, cached_at, expires_at, stale_until }, status, failure }
+}
+
+/// Finds the cache entry for one upstream request: a fresh or stale entry as
+/// cached, otherwise a new one filled from upstream, falling back to the stale
+/// entry when the upstream fails. `wait` is passed on to [`fill`].
+async fn lookup(
+    endpoint: &'static Endpoint,
+    env: &Env,
+    worker_ctx: &Context,
+    request: ProxyRequest,
+    wait: bool,
+) -> Result<Served> {
+    let cache_key = endpoint.cache_key(&request);
+    let cache_manager = CacheManager::new(env)?;
+
+    let cached = cache_manager.get(&cache_key).await?;
+    let freshness = cached.as_ref().map(|c| cache_manager.freshness(c));
+
+    if let (Some(cached), Some(freshness @ (Freshness::Fresh | Freshness::Stale))) = (&cached, freshness) {
+        let status = if freshness == Freshness::Stale {
+            worker_ctx.wait_until(revalidate(endpoint, env.clone(), request.clone(), cache_key, cached.clone()));
+            "STALE"
+        } else {
+            "HIT"
+        };
+        return Ok(Served { entry: cached.clone(), status, failure: None });
+    }
+
+    let filled = FILLS
+        .with(Rc::clone)
+        .run(&cache_key, fill(endpoint, env.clone(), request, cache_key.clone(), cached.clone(), wait))
+        .await;
+
+    match filled {
+        Ok((entry, status)) => Ok(Served { entry, status, failure: None }),
+        Err(e) if e.status_code() >= 500 => match cached {
+            Some(cached) => {
+                console_error!("Serving stale {} after upstream failure: {}", cache_key, e);
+                Ok(Served { entry: cached, status: "STALE-ERROR", failure: Some(e.code()) })
+            }
+            None => Err(e),
+        },
+        Err(e) => Err(e),
+    }
+}
+
+/// Payload served for a cached one on `version`: the payload itself, or its
+/// normalized form (see [`Endpoint::normalizer`]), then refined by the
+/// endpoint's [`Endpoint::refine`] and trimmed to the request's `fields=`
+/// projection. A payload that doesn't fit the endpoint's model is
+/// reported as an upstream failure.
+fn render<'a>(
+    endpoint: &Endpoint,
+    version: Version,
+    request: &ProxyRequest,
+    data: &'a serde_json::Value,
+) -> Result<Cow<'a, serde_json::Value>> {
+    let payload = match endpoint.normalizer(version, request) {
+        Some(normalizer) => Cow::Owned((normalizer.apply)(data.clone()).map_err(|e| NasaApiError::UpstreamUnavailable {
+            upstream: endpoint.source.label,
+            detail: format!("response does not match the model: {e}"),
+        })?),
+        None => Cow::Borrowed(data),
+    };
+    let refined = endpoint.local.iter().any(|param| request.option(param.name).is_some());
+    let payload = match endpoint.refine {
+        Some(refine) if refined => Cow::Owned(refine(request, payload.into_owned())),
+        _ => payload,
+    };
+
+    match request.projection() {
+        Some(projection) => {
+            let projection = projection.map_err(|message| NasaApiError::Validation(vec![Problem::new(FIELDS_PARAM, message)]))?;
+            Ok(Cow::Owned(projection.apply(&payload).unwrap_or(serde_json::Value::Null)))
+        }
+        None => Ok(payload),
+    }
+}
+
+/// Response body and `ETag` for a rendered payload. On v2 the payload is
+/// wrapped in an [`ApiResponse`]; its `ETag` is weak and covers `data` only,
+/// so a cache hit still matches the validator handed out on the miss.
+fn body(payload: &serde_json::Value, version: Version, cache_status: &str) -> Result<(String, String)> {
+    let payload_body = serde_json::to_string(payload)?;
+    let etag = http::etag(&payload_body);
+    match version {
+        Version::V1 => Ok((payload_body, etag)),
+        Version::V2 => {
+            let envelope = ApiResponse { data: payload, status: "success".to_string(), cached: cache_status != "MISS" };
+            Ok((serde_json::to_string(&envelope)?, format!("W/{etag}")))
+        }
+    }
+}
+
+/// Builds the response for a cache entry rendered as `payload`, or a `304` when
+/// the request's validators still match it.
+fn respond(
+    req: &Request,
+    cached: &CachedResponse,
+    payload: &serde_json::Value,
+    version: Version,
+    cache_status: &str,
+) -> Result<Response> {
+    let (body, etag) = body(payload, version, cache_status)?;
+    let now = Utc::now();
+
+    let headers = req.headers();
+    let unchanged = http::not_modified(
+        headers.get("If-None-Match")?.as_deref(),
+        headers.get("If-Modified-Since")?.as_deref(),
+        &etag,
+        cached.cached_at,
+    );
+
+    let mut response = if unchanged {
+        Response::empty()?.with_status(304)
+    } else {
+        let mut response = Response::ok(body)?;
+        response.headers_mut().set("Content-Type", "application/json")?;
+        response
+    };
+
+    let response_headers = response.headers_mut();
+    response_headers.set("ETag", &etag)?;
+    response_headers.set("Last-Modified", &http::http_date(cached.cached_at))?;
+    response_headers.set("Cache-Control", &http::cache_control(cached.expires_at, now))?;
+    response_headers.set("Age", &http::age(cached.cached_at, now))?;
+    response_headers.set("X-Cache-Status", cache_status)?;
+    Ok(response)
+}
+
+/// An entry obtained by [`fill`] and the `X-Cache-Status` it is served with.
+type Filled = Result<(CachedResponse, &'static str)>;
+
+thread_local! {
+    /// Fills in flight in this isolate, keyed by cache key.
+    static FILLS: Rc<SingleFlight<Filled>> = Rc::default();
+}
+
+/// Fetches `endpoint` upstream and caches the result (`MISS`), unless another
+/// isolate holds the fill lock for the key. Then the `stale` entry is served if
+/// there is one (`STALE`); otherwise the request waits briefly for the other
+/// isolate's result (`HIT`) before fetching itself.
+///
+/// Chunks of a split request don't `wait`: polling costs up to eight more KV
+/// reads per chunk, which the split's subrequest budget doesn't allow for
+/// (see [`crate::neo_feed`]). They fetch straight away instead.
+async fn fill(
+    endpoint: &'static Endpoint,
+    env: Env,
+    request: ProxyRequest,
+    cache_key: String,
+    stale: Option<CachedResponse>,
+    wait: bool,
+) -> Filled {
+    let cache_manager = CacheManager::new(&env)?;
+
+    let locked = cache_manager.try_lock(&cache_key).await;
+    if !locked {
+        if let Some(stale) = stale {
+            return Ok((stale, "STALE"));
+        }
+        if wait {
+            if let Some(filled) = cache_manager.wait_for_fill(&cache_key).await? {
+                return Ok((filled, "HIT"));
+            }
+        }
+    }
+
+    let result = async {
+        let value = fetch(endpoint, &UpstreamClient::from_env(&env), &request).await?;
+        Ok(cache_manager.set(&cache_key, value, endpoint.ttl_minutes).await?)
+    }
+    .await;
+
+    if locked {
+        cache_manager.unlock(&cache_key).await;
+    }
+    result.map(|entry| (entry, "MISS"))
+}
+
+/// Makes sure `request` has a fresh cache entry (one per chunk for endpoints
+/// with a [`Split`]), fetching it unless one exists. Used by the scheduled
+/// pre-warmer; returns the `X-Cache-Status` a client would have seen.
+pub async fn warm(endpoint: &'static Endpoint, env: &Env, request: ProxyRequest) -> Result<&'static str> {
+    if endpoint.format == Format::Binary {
+        return Err(NasaApiError::BadRequest(format!("{} responses are not cached", endpoint.route)));
+    }
+
+    let chunks = chunks(endpoint, &request);
+    let wait = chunks.len() == 1;
+    let statuses = join_all(chunks.into_iter().map(|chunk| warm_chunk(endpoint, env, chunk, wait))).await;
+    let statuses = statuses.into_iter().collect::<Result<Vec<_>>>()?;
+    Ok(STATUSES.iter().copied().find(|status| statuses.contains(status)).unwrap_or("HIT"))
+}
+
+async fn warm_chunk(endpoint: &'static Endpoint, env: &Env, request: ProxyRequest, wait: bool) -> Result<&'static str> {
+    let cache_key = endpoint.cache_key(&request);
+    let cache_manager = CacheManager::new(env)?;
+
+    if let Some(cached) = cache_manager.get(&cache_key).await? {
+        if cache_manager.freshness(&cached) == Freshness::Fresh {
+            return Ok("HIT");
+        }
+    }
+
+    let (_, status) = FILLS
+        .with(Rc::clone)
+        .run(&cache_key, fill(endpoint, env.clone(), request, cache_key.clone(), None, wait))
+        .await?;
+    Ok(status)
+}
+
+/// Refreshes a stale cache entry in the background, logging failures; the stale
+/// entry stays in place until it is replaced or hard-expires. Joins a fill
+/// already in flight, and leaves the key alone while another isolate fills it.
+async fn revalidate(endpoint: &'static Endpoint, env: Env, request: ProxyRequest, cache_key: String, stale: CachedResponse) {
+    let result = FILLS
+        .with(Rc::clone)
+        .run(&cache_key, fill(endpoint, env, request, cache_key.clone(), Some(stale), true))
+        .await;
+
+    if let Err(e) = result {
+        console_error!("Failed to revalidate {}: {}", cache_key, e);
+    }
+}
+
+/// Fetches `endpoint` upstream and decodes the payload according to its [`Format`].
+pub async fn fetch<U: Upstream>(
+    endpoint: &Endpoint,
+    client: &UpstreamClient<U>,
+    request: &ProxyRequest,
+) -> Result<serde_json::Value> {
+    let path = endpoint.upstream_path(request);
+    let query = endpoint.upstream_query(request);
+    let response = client.get(endpoint.source, &path, &query).await?;
+
+    match endpoint.format {
+        Format::Tap if request.param("format") != Some("json") => Ok(serde_json::json!({
+            "format": request.param("format"),
+            "data": response.text(),
+        })),
+        _ => response.json().map_err(|e| NasaApiError::UpstreamUnavailable {
+            upstream: endpoint.source.label,
+            detail: format!("invalid JSON response: {e}"),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry;
+    use crate::upstream::tests::FakeUpstream;
+    use futures::executor::block_on;
+
+    fn request(endpoint: &Endpoint, path: &[(&str, &str)], query: &[(&str, &str)]) -> ProxyRequest {
+        ProxyRequest::new(
+            endpoint,
+            path.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            query.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        )
+    }
+
+    #[test]
+    fn fetches_templated_path_with_api_key() {
+        let endpoint = registry::find("/api/mars-photos/:rover/photos").unwrap();
+        let fake = FakeUpstream::json(200, r#"{"photos": []}"#);
+        let client = UpstreamClient::new(&fake, Some("KEY".to_string()));
+
+        let value = block_on(fetch(endpoint, &client, &request(endpoint, &[("rover", "curiosity")], &[("sol", "1000")]))).unwrap();
+
+        assert_eq!(value, serde_json::json!({"photos": []}));
+        assert_eq!(
+            fake.requests.borrow()[0],
+            "https://api.nasa.gov/mars-photos/api/v1/rovers/curiosity/photos?sol=1000&api_key=KEY"
+        );
+    }
+
+    #[test]
+    fn malformed_upstream_json_is_a_gateway_error() {
+        let endpoint = registry::find("/api/apod").unwrap();
+        let fake = FakeUpstream::json(200, "<html>maintenance</html>");
+        let client = UpstreamClient::new(&fake, Some("KEY".to_string()));
+
+        let err = block_on(fetch(endpoint, &client, &request(endpoint, &[], &[]))).unwrap_err();
+
+        assert_eq!(err.status_code(), 502);
+        assert_eq!(err.upstream(), Some("NASA API"));
+    }
+
+    #[test]
+    fn renders_normalized_payloads_on_request() {
+        let endpoint = registry::find("/api/epic/natural/all").unwrap();
+        let data = serde_json::json!([{"date": "2024-01-05", "extra": 1}]);
+
+        let raw = render(endpoint, Version::V1, &request(endpoint, &[], &[]), &data).unwrap();
+        let normalized = render(endpoint, Version::V1, &request(endpoint, &[], &[("normalize", "yes")]), &data).unwrap();
+        let v2 = render(endpoint, Version::V2, &request(endpoint, &[], &[]), &data).unwrap();
+
+        assert!(matches!(raw, Cow::Borrowed(_)));
+        assert_eq!(*normalized, serde_json::json!([{"date": "2024-01-05"}]));
+        assert_eq!(v2, normalized);
+    }
+
+    #[test]
+    fn projects_the_rendered_payload() {
+        let endpoint = registry::find("/api/neo/browse").unwrap();
+        let data = serde_json::json!({
+            "links": {"self": "https://api.nasa.gov/neo/rest/v1/neo/browse"},
+            "page": {"size": 20, "total_elements": 1, "total_pages": 1, "number": 0},
+            "near_earth_objects": []
+        });
+
+        let v1 = render(endpoint, Version::V1, &request(endpoint, &[], &[("fields", "page.number")]), &data).unwrap();
+        let v2 = render(endpoint, Version::V2, &request(endpoint, &[], &[("fields", "/page,total_pages")]), &data).unwrap();
+
+        assert_eq!(*v1, serde_json::json!({"page": {"number": 0}}));
+        assert_eq!(*v2, serde_json::json!({"page": 0, "total_pages": 1}));
+    }
+
+    #[test]
+    fn payloads_that_do_not_fit_the_model_are_a_gateway_error() {
+        let endpoint = registry::find("/api/donki/flr").unwrap();
+
+        let err = render(endpoint, Version::V2, &request(endpoint, &[], &[]), &serde_json::json!({"oops": true})).unwrap_err();
+
+        assert_eq!(err.status_code(), 502);
+        assert_eq!(err.upstream(), Some("NASA API"));
+    }
+
+    #[test]
+    fn v1_bodies_are_the_payload_and_v2_bodies_an_envelope() {
+        let payload = serde_json::json!({"title": "Orion"});
+
+        let (v1, v1_etag) = body(&payload, Version::V1, "MISS").unwrap();
+        let (miss, miss_etag) = body(&payload, Version::V2, "MISS").unwrap();
+        let (hit, hit_etag) = body(&payload, Version::V2, "HIT").unwrap();
+
+        assert_eq!(v1, r#"{"title":"Orion"}"#);
+        assert_eq!(miss, r#"{"data":{"title":"Orion"},"status":"success","cached":false}"#);
+        assert_eq!(hit, r#"{"data":{"title":"Orion"},"status":"success","cached":true}"#);
+        assert_eq!(miss_etag, hit_etag);
+        assert_eq!(miss_etag, format!("W/{v1_etag}"));
+    }
+
+    #[test]
+    fn combines_chunks_into_one_entry() {
+        let split = registry::find("/api/neo/feed").unwrap().split.unwrap();
+        let at = |hour| Utc::now().date_naive().and_hms_opt(hour, 0, 0).unwrap().and_utc();
+        let part = |day: &str, status, cached_at, expires_at| Served {
+            entry: CachedResponse {
+                data: serde_json::json!({"links": {}, "element_count": 1, "near_earth_objects": {day: [{"id": day}]}}),
+                cached_at,
+                expires_at,
+                stale_until: None,
+            },
+            status,
+            failure: None,
+        };
+
+        let served = combine(&split, vec![part("2024-01-01", "HIT", at(1), at(3)), part("2024-01-09", "STALE", at(2), at(4))]);
+
+        assert_eq!(served.status, "STALE");
+        assert_eq!(served.entry.cached_at, at(2));
+        assert_eq!(served.entry.expires_at, at(3));
+        assert_eq!(served.entry.data["element_count"], 2);
+        assert_eq!(combine(&split, vec![part("2024-01-01", "MISS", at(1), at(3)), part("2024-01-09", "STALE-ERROR", at(2), at(4))]).status, "MISS");
+    }
+
+    #[test]
+    fn wraps_non_json_tap_output() {
+        let endpoint = registry::find("/api/exoplanets/query").unwrap();
+        let fake = FakeUpstream::json(200, "pl_name\nKepler-22 b\n");
+        let client = UpstreamClient::new(&fake, None);
+
+        let value = block_on(fetch(endpoint, &client, &request(endpoint, &[], &[("query", "select pl_name from ps"), ("format", "csv")]))).unwrap();
+
+        assert_eq!(value, serde_json::json!({"format": "csv", "data": "pl_name\nKepler-22 b\n"}));
+    }
+}
@@ -0,0 +1,89 @@
+//! Usage reports built from the counters kept by [`crate::middleware::usage`].
+
+use chrono::{Duration, NaiveDate, Utc};
+use serde::Serialize;
+use serde_json::json;
+use worker::{Request, Response, RouteContext};
+
+use crate::error::{NasaApiError, Result};
+use crate::middleware::auth;
+use crate::middleware::usage::{DailyUsage, RouteUsage, UsageTracker, RETENTION_DAYS};
+use crate::utils;
+use super::HandlerContext;
+
+/// Most clients read by one `/api/admin/usage` call; each costs a KV read.
+const MAX_USAGE_CLIENTS: usize = 500;
+
+/// A day of usage together with its totals over all routes.
+#[derive(Serialize)]
+struct UsageReport<'a> {
+    #[serde(flatten)]
+    usage: &'a DailyUsage,
+    total: RouteUsage,
+}
+
+impl<'a> UsageReport<'a> {
+    fn new(usage: &'a DailyUsage) -> Self {
+        Self { usage, total: usage.total() }
+    }
+}
+
+/// `GET /api/usage` — the caller's own usage for today and, with `days=N`, the
+/// `N - 1` days before it.
+pub async fn get_usage(req: Request, ctx: RouteContext<HandlerContext>) -> Result<Response> {
+    let (env, _, client) = &ctx.data;
+    let client = client
+        .as_ref()
+        .ok_or_else(|| NasaApiError::Internal("Usage requested without an authenticated client".to_string()))?;
+
+    let days = match utils::query_param(&req, "days")? {
+        Some(days) => days
+            .parse::<i64>()
+            .ok()
+            .filter(|days| (1..=RETENTION_DAYS).contains(days))
+            .ok_or_else(|| NasaApiError::BadRequest(format!("'days' must be between 1 and {RETENTION_DAYS}")))?,
+        None => 1,
+    };
+
+    let tracker = UsageTracker::new(env)?;
+    let today = Utc::now().date_naive();
+    let mut records = Vec::new();
+    for offset in 0..days {
+        if let Some(usage) = tracker.get(&client.id, today - Duration::days(offset)).await? {
+            records.push(usage);
+        }
+    }
+
+    Ok(Response::from_json(&json!({
+        "client": client.id,
+        "tier": client.tier,
+        "days": records.iter().map(UsageReport::new).collect::<Vec<_>>(),
+    }))?)
+}
+
+/// `GET /api/admin/usage` — usage on `date` (default today) of at most
+/// [`MAX_USAGE_CLIENTS`] clients per call, heaviest consumers first. When
+/// `complete` is false, calling again with the returned `cursor` reports the
+/// next clients. Requires the admin token.
+pub async fn get_all_usage(req: Request, ctx: RouteContext<HandlerContext>) -> Result<Response> {
+    let (env, _, _) = &ctx.data;
+    auth::require_admin(&req, env)?;
+
+    let date = match utils::query_param(&req, "date")? {
+        Some(date) => NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|_| NasaApiError::BadRequest("Invalid date format. Use YYYY-MM-DD".to_string()))?,
+        None => Utc::now().date_naive(),
+    };
+
+    let cursor = utils::query_param(&req, "cursor")?.filter(|cursor| !cursor.is_empty());
+
+    let (mut records, cursor) = UsageTracker::new(env)?.all(date, cursor, MAX_USAGE_CLIENTS).await?;
+    records.sort_by_key(|usage| std::cmp::Reverse(usage.total().requests));
+
+    Ok(Response::from_json(&json!({
+        "date": date,
+        "clients": records.iter().map(UsageReport::new).collect::<Vec<_>>(),
+        "complete": cursor.is_none(),
+        "cursor": cursor,
+    }))?)
+}
@@ -17,7 +17,7 @@
 #[macro_export]
 macro_rules! wrap_handler {
     ($handler:expr) => {
-        |req, ctx| async move {
+        move |req, ctx| async move {
             match $handler(req, ctx).await {
                 Ok(response) => Ok(response),
                 Err(e) => Ok(e.to_response()),
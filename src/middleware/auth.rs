@@ -0,0 +1,303 @@
+//! Client API keys for the proxy itself, backed by the `API_KEYS` KV namespace.
+//!
+//! Keys are never stored in plain text: each entry lives under
+//! `key:<fingerprint>` (see [`fingerprint`]) and holds a [`ClientKey`] describing
+//! who owns it, which quota [`Tier`] it gets and which routes it may call. A key
+//! is revoked by setting `enabled` to `false` or deleting its entry; the upstream
+//! `NASA_API_KEY` is unaffected either way.
+//!
+//! Presenting a key is optional unless `REQUIRE_CLIENT_KEY` is `true`, in which
+//! case anonymous requests outside [`EXEMPT_PREFIXES`] are rejected with a 401.
+//! Admin routes are authenticated separately with the `ADMIN_TOKEN` secret; see
+//! [`require_admin`].
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use url::Url;
+use worker::{kv::KvStore, Env, Request};
+
+use crate::error::{NasaApiError, Result};
+use crate::middleware::client::{bearer_token, fingerprint, tokens_match, Client, Tier, API_KEY_HEADER};
+use crate::registry::Version;
+use crate::utils::get_client_ip;
+
+/// Query parameter accepted as an alternative to the [`API_KEY_HEADER`] header.
+pub const API_KEY_PARAM: &str = "key";
+
+/// Routes that stay reachable without a client key even when one is required.
+const EXEMPT_PREFIXES: &[&str] = &["/api/docs", "/api/admin"];
+
+/// Routes every enabled key may call, whatever its `allowed_routes`.
+const SELF_SERVICE_PREFIXES: &[&str] = &["/api/docs", "/api/usage"];
+
+/// Metadata stored for one client key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientKey {
+    /// Person or application the key was issued to.
+    pub owner: String,
+    #[serde(default = "default_tier")]
+    pub tier: Tier,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Route prefixes the key may call, e.g. `/api/apod`; empty allows every route.
+    #[serde(default)]
+    pub allowed_routes: Vec<String>,
+}
+
+fn default_tier() -> Tier {
+    Tier::Keyed
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl ClientKey {
+    /// Checks that the key may be used for a request to `path`.
+    pub fn authorize(&self, path: &str) -> Result<()> {
+        if !self.enabled {
+            return Err(NasaApiError::Authentication("API key has been revoked".to_string()));
+        }
+
+        let allowed = self.allowed_routes.is_empty()
+            || SELF_SERVICE_PREFIXES.iter().any(|prefix| matches_prefix(path, prefix))
+            || self.allowed_routes.iter().any(|prefix| matches_prefix(path, prefix));
+        if !allowed {
+            return Err(NasaApiError::Forbidden(format!("API key is not allowed to access {path}")));
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether `path` is `prefix` itself or lies below it (`/api/donki` matches
+/// `/api/donki/cme` but not `/api/donkified`).
+fn matches_prefix(path: &str, prefix: &str) -> bool {
+    let prefix = prefix.trim_end_matches('/');
+    path.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Where client key metadata is looked up.
+#[async_trait(?Send)]
+pub trait KeyLookup {
+    /// Metadata for a presented key, if it is known.
+    async fn get(&self, key: &str) -> Result<Option<ClientKey>>;
+}
+
+/// Read access to the `API_KEYS` namespace.
+pub struct KeyStore {
+    kv: KvStore,
+}
+
+impl KeyStore {
+    pub fn new(env: &Env) -> Result<Self> {
+        let kv = env.kv("API_KEYS")
+            .map_err(|e| NasaApiError::Internal(format!("Failed to get API key store: {e}")))?;
+
+        Ok(Self { kv })
+    }
+
+    /// KV key holding the metadata for the client key with this fingerprint.
+    pub fn entry_key(fingerprint: &str) -> String {
+        format!("key:{fingerprint}")
+    }
+}
+
+#[async_trait(?Send)]
+impl KeyLookup for KeyStore {
+    async fn get(&self, key: &str) -> Result<Option<ClientKey>> {
+        self.kv
+            .get(&Self::entry_key(&fingerprint(key)))
+            .json::<ClientKey>()
+            .await
+            .map_err(|e| NasaApiError::Internal(format!("Failed to read API key store: {e}")))
+    }
+}
+
+/// What a request presents to identify its client.
+#[derive(Debug, Clone, Default)]
+pub struct Credentials {
+    /// Path of the request, without its [`Version`] prefix.
+    pub path: String,
+    /// Token from an `Authorization: Bearer` header.
+    pub bearer: Option<String>,
+    /// Client key from the [`API_KEY_HEADER`] header or the `key` query parameter.
+    pub key: Option<String>,
+    /// Address of the caller, identifying anonymous clients.
+    pub ip: String,
+}
+
+impl Credentials {
+    pub fn from_request(req: &Request) -> Result<Self> {
+        let header = req.headers().get(API_KEY_HEADER).ok().flatten();
+        Ok(Self::from_parts(&req.url()?, header, bearer_token(req), get_client_ip(req)))
+    }
+
+    /// Credentials of a request to `url` with the given header values.
+    pub fn from_parts(url: &Url, api_key_header: Option<String>, bearer: Option<String>, ip: String) -> Self {
+        let from_query = || {
+            url.query_pairs()
+                .find(|(name, _)| name == API_KEY_PARAM)
+                .map(|(_, value)| value.into_owned())
+        };
+        Self {
+            path: Version::unversioned(url.path()).into_owned(),
+            bearer,
+            key: api_key_header.or_else(from_query).filter(|key| !key.trim().is_empty()),
+            ip,
+        }
+    }
+}
+
+fn key_required(env: &Env) -> bool {
+    env.var("REQUIRE_CLIENT_KEY")
+        .map(|v| v.to_string().eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// Works out which client sent `req`, rejecting unknown, revoked or out-of-scope keys.
+/// Scopes cover a route on every [`Version`] it is served on.
+///
+/// Callers presenting the `INTERNAL_API_TOKEN` secret as a bearer token are
+/// internal; callers with a valid key get the key's tier; everyone else is
+/// anonymous and identified by IP address.
+pub async fn authenticate(req: &Request, env: &Env) -> Result<Client> {
+    let internal_token = env.secret("INTERNAL_API_TOKEN").ok().map(|s| s.to_string());
+    identify(&Credentials::from_request(req)?, internal_token.as_deref(), key_required(env), || KeyStore::new(env)).await
+}
+
+/// [`authenticate`] for given credentials. The key store is only opened when a
+/// key has to be looked up.
+pub async fn identify<K: KeyLookup>(
+    credentials: &Credentials,
+    internal_token: Option<&str>,
+    key_required: bool,
+    keys: impl FnOnce() -> Result<K>,
+) -> Result<Client> {
+    if let (Some(token), Some(expected)) = (&credentials.bearer, internal_token) {
+        if tokens_match(token, expected) {
+            return Ok(Client { id: "internal".to_string(), tier: Tier::Internal });
+        }
+    }
+
+    let path = &credentials.path;
+    match &credentials.key {
+        Some(key) => {
+            let entry = keys()?
+                .get(key)
+                .await?
+                .ok_or_else(|| NasaApiError::Authentication("Invalid API key".to_string()))?;
+            entry.authorize(path)?;
+
+            Ok(Client { id: format!("key:{}", fingerprint(key)), tier: entry.tier })
+        }
+        None if key_required && !EXEMPT_PREFIXES.iter().any(|prefix| matches_prefix(path, prefix)) => {
+            Err(NasaApiError::Authentication(format!(
+                "An API key is required; send it in the {API_KEY_HEADER} header or the '{API_KEY_PARAM}' query parameter"
+            )))
+        }
+        None => Ok(Client { id: credentials.ip.clone(), tier: Tier::Anonymous }),
+    }
+}
+
+/// Rejects requests that don't carry the `ADMIN_TOKEN` secret as a bearer token.
+///
+/// Admin routes are disabled (every request is rejected) while the secret is unset.
+pub fn require_admin(req: &Request, env: &Env) -> Result<()> {
+    let expected = env.secret("ADMIN_TOKEN").map(|s| s.to_string()).unwrap_or_default();
+
+    match bearer_token(req) {
+        Some(token) if tokens_match(&token, &expected) => Ok(()),
+        _ => Err(NasaApiError::Authentication("A valid admin token is required".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    /// A key store that knows no keys.
+    struct NoKeys;
+
+    #[async_trait(?Send)]
+    impl KeyLookup for NoKeys {
+        async fn get(&self, _key: &str) -> Result<Option<ClientKey>> {
+            Ok(None)
+        }
+    }
+
+    fn credentials(url: &str) -> Credentials {
+        let url = Url::parse(url).unwrap();
+        Credentials::from_parts(&url, None, Some("admin-token".to_string()), "203.0.113.7".to_string())
+    }
+
+    fn key(enabled: bool, allowed_routes: &[&str]) -> ClientKey {
+        ClientKey {
+            owner: "ios-app".to_string(),
+            tier: Tier::Keyed,
+            enabled,
+            allowed_routes: allowed_routes.iter().map(|r| r.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn deserializes_minimal_entry_with_defaults() {
+        let entry: ClientKey = serde_json::from_str(r#"{"owner": "ios-app"}"#).unwrap();
+
+        assert_eq!(entry, key(true, &[]));
+    }
+
+    #[test]
+    fn revoked_key_is_unauthorized() {
+        let err = key(false, &[]).authorize("/api/apod").unwrap_err();
+
+        assert_eq!(err.status_code(), 401);
+    }
+
+    #[test]
+    fn allowed_routes_match_whole_segments() {
+        let entry = key(true, &["/api/apod", "/api/donki/"]);
+
+        assert!(entry.authorize("/api/apod").is_ok());
+        assert!(entry.authorize("/api/donki/cme").is_ok());
+        assert!(entry.authorize("/api/usage").is_ok());
+        assert_eq!(entry.authorize("/api/apodx").unwrap_err().status_code(), 403);
+        assert_eq!(entry.authorize("/api/neo/feed").unwrap_err().status_code(), 403);
+    }
+
+    #[test]
+    fn cache_purges_by_key_are_not_taken_for_client_keys() {
+        let purge = credentials("https://nasa.example.com/api/admin/cache?cache_key=apod:date=2024-01-05");
+        let client = block_on(identify(&purge, Some("internal-token"), true, || Ok(NoKeys))).unwrap();
+
+        assert_eq!(client, Client { id: "203.0.113.7".to_string(), tier: Tier::Anonymous });
+
+        let misnamed = credentials("https://nasa.example.com/api/admin/cache?key=apod:date=2024-01-05");
+        let err = block_on(identify(&misnamed, Some("internal-token"), true, || Ok(NoKeys))).unwrap_err();
+        assert_eq!(err.status_code(), 401);
+    }
+
+    #[test]
+    fn only_the_exact_internal_token_is_internal() {
+        let internal = |token: Option<&str>| {
+            let client = block_on(identify(&credentials("https://nasa.example.com/api/apod"), token, false, || Ok(NoKeys))).unwrap();
+            client.tier == Tier::Internal
+        };
+
+        assert!(internal(Some("admin-token")));
+        assert!(!internal(Some("admin-token2")));
+        assert!(!internal(Some("admin-toke")));
+        assert!(!internal(Some("")));
+        assert!(!internal(None));
+    }
+
+    #[test]
+    fn versioned_paths_are_authorized_as_unversioned() {
+        let credentials = credentials("https://nasa.example.com/api/v2/apod?key=secret");
+
+        assert_eq!(credentials.path, "/api/apod");
+        assert_eq!(credentials.key.as_deref(), Some("secret"));
+    }
+}
@@ -0,0 +1,66 @@
+//! Identification of the client behind a request.
+//!
+//! Clients fall into one of three [`Tier`]s, each with its own rate limit quota.
+//! How a request is mapped to a client is decided in [`crate::middleware::auth`].
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use worker::Request;
+
+/// Header carrying a client API key.
+pub const API_KEY_HEADER: &str = "X-API-Key";
+
+/// Quota tier a client belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tier {
+    Anonymous,
+    Keyed,
+    Internal,
+}
+
+impl Tier {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Tier::Anonymous => "anonymous",
+            Tier::Keyed => "keyed",
+            Tier::Internal => "internal",
+        }
+    }
+}
+
+/// The caller of a request, as seen by rate limiting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Client {
+    /// Stable identifier: the IP address, a key fingerprint, or `internal`.
+    pub id: String,
+    pub tier: Tier,
+}
+
+/// Short, non-reversible fingerprint of a secret, safe to use in KV keys and logs.
+pub fn fingerprint(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))[..16].to_string()
+}
+
+/// Whether a presented secret matches the expected one, in time that depends on
+/// neither. Both are hashed first so the comparison doesn't leak the length either;
+/// an empty `expected` never matches.
+pub fn tokens_match(presented: &str, expected: &str) -> bool {
+    let presented = Sha256::digest(presented.as_bytes());
+    let expected_digest = Sha256::digest(expected.as_bytes());
+    let difference = presented
+        .iter()
+        .zip(expected_digest.iter())
+        .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+
+    difference == 0 && !expected.is_empty()
+}
+
+/// Token from an `Authorization: Bearer <token>` header.
+pub fn bearer_token(req: &Request) -> Option<String> {
+    req.headers()
+        .get("Authorization")
+        .ok()
+        .flatten()
+        .and_then(|value| value.strip_prefix("Bearer ").map(|token| token.trim().to_string()))
+}
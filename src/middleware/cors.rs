@@ -1,12 +1,14 @@
 use worker::{Headers, Method, Request, Response};
 
+use crate::error::NasaApiError;
+
 pub async fn handle_cors(req: Request) -> worker::Result<Response> {
     let origin = req.headers().get("Origin")?.unwrap_or_default();
     
     let headers = Headers::new();
     headers.set("Access-Control-Allow-Origin", &origin)?;
-    headers.set("Access-Control-Allow-Methods", "GET, POST, OPTIONS")?;
-    headers.set("Access-Control-Allow-Headers", "Content-Type, Authorization")?;
+    headers.set("Access-Control-Allow-Methods", "GET, POST, DELETE, OPTIONS")?;
+    headers.set("Access-Control-Allow-Headers", "Content-Type, Authorization, X-API-Key, If-None-Match, If-Modified-Since")?;
     headers.set("Access-Control-Max-Age", "86400")?; // 24 hours
     
     if req.method() == Method::Options {
@@ -14,15 +16,15 @@ pub async fn handle_cors(req: Request) -> worker::Result<Response> {
     }
     
     // Return 404 for non-OPTIONS requests to catch-all
-    Response::error("Not Found", 404)
+    Ok(NasaApiError::NotFound(format!("No route for {}", req.path())).to_response())
 }
 
 #[allow(dead_code)]
 pub fn add_cors_headers(response: &mut Response, origin: &str) -> worker::Result<()> {
     let headers = response.headers_mut();
     headers.set("Access-Control-Allow-Origin", origin)?;
-    headers.set("Access-Control-Allow-Methods", "GET, POST, OPTIONS")?;
-    headers.set("Access-Control-Allow-Headers", "Content-Type, Authorization")?;
+    headers.set("Access-Control-Allow-Methods", "GET, POST, DELETE, OPTIONS")?;
+    headers.set("Access-Control-Allow-Headers", "Content-Type, Authorization, X-API-Key, If-None-Match, If-Modified-Since")?;
     headers.set("Access-Control-Max-Age", "86400")?;
     Ok(())
 }
\ No newline at end of file
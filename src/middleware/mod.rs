@@ -1,2 +1,5 @@
+pub mod auth;
+pub mod client;
 pub mod cors;
-pub mod rate_limit;
\ No newline at end of file
+pub mod rate_limit;
+pub mod usage;
\ No newline at end of file
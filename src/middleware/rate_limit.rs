@@ -1,8 +1,159 @@
+//! Per-client rate limiting backed by the `RATE_LIMIT` KV namespace.
+//!
+//! Each client is counted in a bucket sized by its [`Tier`]; routes listed in
+//! `RATE_LIMIT_ROUTES` additionally get their own, usually tighter, bucket per
+//! client. How a bucket is counted is chosen with `RATE_LIMIT_ALGORITHM`.
+
+use async_trait::async_trait;
 use chrono::{DateTime, Utc, Duration};
 use serde::{Deserialize, Serialize};
-use worker::{kv::KvStore, Env, Request};
+use worker::{console_error, kv::KvStore, Env, Headers, Response};
 use crate::error::{NasaApiError, Result};
-use crate::utils::get_client_ip;
+use crate::middleware::client::{Client, Tier};
+
+/// Cloudflare KV rejects expiration TTLs shorter than this.
+const MIN_KV_TTL_SECONDS: i64 = 60;
+
+/// Routes with their own bucket unless `RATE_LIMIT_ROUTES` says otherwise.
+const DEFAULT_ROUTE_QUOTAS: &str = "/api/exoplanets/query=10/60,/api/earth/imagery=20/60";
+
+/// Number of requests allowed per window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    pub requests: u32,
+    pub window_seconds: i64,
+}
+
+impl Quota {
+    /// Parses `"<requests>/<seconds>"`; `"0"`, `"unlimited"` and an empty string
+    /// mean no limit and yield `Ok(None)`.
+    pub fn parse(value: &str) -> std::result::Result<Option<Self>, String> {
+        let value = value.trim();
+        if value.is_empty() || value == "0" || value.eq_ignore_ascii_case("unlimited") {
+            return Ok(None);
+        }
+
+        let (requests, window) = value
+            .split_once('/')
+            .ok_or_else(|| format!("invalid quota '{value}', expected <requests>/<seconds>"))?;
+        let requests: u32 = requests.trim().parse().map_err(|_| format!("invalid request count in '{value}'"))?;
+        let window_seconds: i64 = window.trim().parse().map_err(|_| format!("invalid window in '{value}'"))?;
+
+        if requests == 0 {
+            return Ok(None);
+        }
+        if window_seconds <= 0 {
+            return Err(format!("window must be positive in '{value}'"));
+        }
+        Ok(Some(Self { requests, window_seconds }))
+    }
+
+    fn window(&self) -> Duration {
+        Duration::seconds(self.window_seconds)
+    }
+}
+
+/// Counting strategy used for every bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    /// Fixed windows; cheap, but allows bursts of twice the quota across a boundary.
+    FixedWindow,
+    /// Keeps the timestamp of every request in the trailing window.
+    SlidingLog,
+    /// Refills continuously at `requests / window`, bursting up to `requests`.
+    TokenBucket,
+}
+
+impl Algorithm {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "fixed" | "fixed_window" => Some(Self::FixedWindow),
+            "sliding" | "sliding_log" => Some(Self::SlidingLog),
+            "token_bucket" | "bucket" => Some(Self::TokenBucket),
+            _ => None,
+        }
+    }
+
+    /// Applies one request to a bucket, returning the updated state and status.
+    ///
+    /// State written by a different algorithm is discarded, so switching
+    /// algorithms simply starts every bucket afresh.
+    pub fn record(&self, state: Option<BucketState>, now: DateTime<Utc>, quota: Quota) -> (BucketState, RateLimitStatus) {
+        match self {
+            Algorithm::FixedWindow => {
+                let mut info = match state {
+                    // Still in the same window
+                    Some(BucketState::Fixed(info)) if now - info.window_start < quota.window() => info,
+                    // First request, or a new window
+                    _ => RateLimitInfo { count: 0, window_start: now },
+                };
+
+                let allowed = info.count < quota.requests;
+                if allowed {
+                    info.count += 1;
+                }
+
+                let status = RateLimitStatus {
+                    allowed,
+                    limit: quota.requests,
+                    remaining: quota.requests.saturating_sub(info.count),
+                    reset: (info.window_start + quota.window()).timestamp(),
+                };
+                (BucketState::Fixed(info), status)
+            }
+            Algorithm::SlidingLog => {
+                let cutoff = (now - quota.window()).timestamp_millis();
+                let mut hits = match state {
+                    Some(BucketState::Sliding { hits }) => hits,
+                    _ => Vec::new(),
+                };
+                hits.retain(|hit| *hit > cutoff);
+
+                let allowed = hits.len() < quota.requests as usize;
+                if allowed {
+                    hits.push(now.timestamp_millis());
+                }
+
+                // A slot frees up when the oldest request leaves the window
+                let oldest = hits.first().copied().unwrap_or(now.timestamp_millis());
+                let status = RateLimitStatus {
+                    allowed,
+                    limit: quota.requests,
+                    remaining: quota.requests.saturating_sub(hits.len() as u32),
+                    reset: (oldest + quota.window_seconds * 1000 + 999).div_euclid(1000),
+                };
+                (BucketState::Sliding { hits }, status)
+            }
+            Algorithm::TokenBucket => {
+                let capacity = quota.requests as f64;
+                let per_second = capacity / quota.window_seconds as f64;
+                let mut tokens = match state {
+                    Some(BucketState::Token { tokens, updated_at }) => {
+                        let elapsed = (now - updated_at).num_milliseconds().max(0) as f64 / 1000.0;
+                        (tokens + elapsed * per_second).min(capacity)
+                    }
+                    _ => capacity,
+                };
+
+                let allowed = tokens >= 1.0;
+                if allowed {
+                    tokens -= 1.0;
+                }
+
+                // Blocked clients can retry once a whole token has accrued
+                let target = if allowed { capacity } else { 1.0 };
+                let wait = ((target - tokens).max(0.0) / per_second).ceil() as i64;
+                let status = RateLimitStatus {
+                    allowed,
+                    limit: quota.requests,
+                    remaining: tokens.floor() as u32,
+                    reset: now.timestamp() + wait,
+                };
+                (BucketState::Token { tokens, updated_at: now }, status)
+            }
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RateLimitInfo {
@@ -10,74 +161,439 @@ pub struct RateLimitInfo {
     pub window_start: DateTime<Utc>,
 }
 
-#[allow(dead_code)]
-pub struct RateLimiter {
+/// Persisted state of one bucket, tagged with the algorithm that wrote it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "algorithm", rename_all = "snake_case")]
+pub enum BucketState {
+    Fixed(RateLimitInfo),
+    Sliding {
+        /// Request times in Unix milliseconds, oldest first.
+        hits: Vec<i64>,
+    },
+    Token {
+        tokens: f64,
+        updated_at: DateTime<Utc>,
+    },
+}
+
+/// Rate limiting configuration read from the worker's vars.
+///
+/// | Variable | Default |
+/// |----------|---------|
+/// | `RATE_LIMIT_ALGORITHM` | `sliding` (or `fixed`, `token_bucket`) |
+/// | `RATE_LIMIT_ANONYMOUS` | `100/60` |
+/// | `RATE_LIMIT_KEYED` | `1000/60` |
+/// | `RATE_LIMIT_INTERNAL` | `unlimited` |
+/// | `RATE_LIMIT_ROUTES` | `/api/exoplanets/query=10/60,/api/earth/imagery=20/60` |
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitConfig {
+    pub algorithm: Algorithm,
+    pub anonymous: Option<Quota>,
+    pub keyed: Option<Quota>,
+    pub internal: Option<Quota>,
+    /// Route prefixes with their own per-client bucket.
+    pub routes: Vec<(String, Quota)>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            algorithm: Algorithm::SlidingLog,
+            anonymous: Some(Quota { requests: 100, window_seconds: 60 }),
+            keyed: Some(Quota { requests: 1000, window_seconds: 60 }),
+            internal: None,
+            routes: parse_routes(DEFAULT_ROUTE_QUOTAS),
+        }
+    }
+}
+
+/// Parses `"<prefix>=<quota>,..."`, skipping (and logging) malformed entries.
+fn parse_routes(value: &str) -> Vec<(String, Quota)> {
+    value
+        .split(',')
+        .filter(|entry| !entry.trim().is_empty())
+        .filter_map(|entry| {
+            let parsed = entry
+                .split_once('=')
+                .ok_or_else(|| format!("invalid route quota '{entry}'"))
+                .and_then(|(route, quota)| Ok((route.trim().to_string(), Quota::parse(quota)?)));
+            match parsed {
+                Ok((route, quota)) => quota.map(|quota| (route, quota)),
+                Err(e) => {
+                    console_error!("Ignoring RATE_LIMIT_ROUTES entry: {}", e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+impl RateLimitConfig {
+    pub fn from_env(env: &Env) -> Self {
+        let defaults = Self::default();
+        let var = |name: &str| env.var(name).ok().map(|v| v.to_string());
+        let quota = |name: &str, default: Option<Quota>| match var(name).map(|v| Quota::parse(&v)) {
+            Some(Ok(quota)) => quota,
+            Some(Err(e)) => {
+                console_error!("Ignoring {}: {}", name, e);
+                default
+            }
+            None => default,
+        };
+
+        Self {
+            algorithm: var("RATE_LIMIT_ALGORITHM")
+                .and_then(|v| Algorithm::parse(&v))
+                .unwrap_or(defaults.algorithm),
+            anonymous: quota("RATE_LIMIT_ANONYMOUS", defaults.anonymous),
+            keyed: quota("RATE_LIMIT_KEYED", defaults.keyed),
+            internal: quota("RATE_LIMIT_INTERNAL", defaults.internal),
+            routes: var("RATE_LIMIT_ROUTES").map(|v| parse_routes(&v)).unwrap_or(defaults.routes),
+        }
+    }
+
+    pub fn tier_quota(&self, tier: Tier) -> Option<Quota> {
+        match tier {
+            Tier::Anonymous => self.anonymous,
+            Tier::Keyed => self.keyed,
+            Tier::Internal => self.internal,
+        }
+    }
+
+    /// Buckets a request from `client` to `path` is counted against, as
+    /// `(KV key, quota)` pairs. Unlimited tiers get no buckets at all.
+    pub fn buckets(&self, client: &Client, path: &str) -> Vec<(String, Quota)> {
+        let Some(tier_quota) = self.tier_quota(client.tier) else {
+            return Vec::new();
+        };
+
+        let base = format!("rate_limit:{}:{}", client.tier.as_str(), client.id);
+        let mut buckets = vec![(base.clone(), tier_quota)];
+        buckets.extend(
+            self.routes
+                .iter()
+                .filter(|(route, _)| path.starts_with(route.as_str()))
+                .map(|(route, quota)| (format!("{base}:{route}"), *quota)),
+        );
+        buckets
+    }
+}
+
+/// Outcome of a rate limit check, reported to clients through response headers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// Unix timestamp (seconds) at which the bucket next frees up capacity.
+    pub reset: i64,
+}
+
+impl RateLimitStatus {
+    /// Combines the statuses of several buckets: blocked if any bucket is, and
+    /// otherwise reporting the bucket closest to its limit.
+    pub fn most_restrictive(statuses: impl IntoIterator<Item = Self>) -> Option<Self> {
+        statuses
+            .into_iter()
+            .min_by_key(|status| (status.allowed, status.remaining, std::cmp::Reverse(status.reset)))
+    }
+
+    /// Seconds until the window resets, never less than one.
+    pub fn retry_after(&self, now: DateTime<Utc>) -> i64 {
+        (self.reset - now.timestamp()).max(1)
+    }
+
+    /// Adds the `X-RateLimit-*` headers (and `Retry-After` when blocked).
+    pub fn apply(&self, headers: &Headers) -> worker::Result<()> {
+        headers.set("X-RateLimit-Limit", &self.limit.to_string())?;
+        headers.set("X-RateLimit-Remaining", &self.remaining.to_string())?;
+        headers.set("X-RateLimit-Reset", &self.reset.to_string())?;
+        if !self.allowed {
+            headers.set("Retry-After", &self.retry_after(Utc::now()).to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Builds the 429 JSON response returned when the limit is exceeded.
+    pub fn error_response(&self) -> worker::Result<Response> {
+        let mut response = NasaApiError::RateLimit.to_response();
+        self.apply(response.headers_mut())?;
+        Ok(response)
+    }
+}
+
+/// Where bucket states live between requests.
+#[async_trait(?Send)]
+pub trait BucketStore {
+    async fn load(&self, key: &str) -> Result<Option<BucketState>>;
+    /// Stores a bucket's new state. Failures are the store's to log: a bucket
+    /// that can't be written still counts for this request.
+    async fn save(&self, key: &str, state: &BucketState, ttl_seconds: u64);
+}
+
+/// Bucket states shared by all isolates through the `RATE_LIMIT` KV namespace.
+pub struct KvBucketStore {
     kv: KvStore,
-    requests_per_hour: u32,
 }
 
-#[allow(dead_code)]
-impl RateLimiter {
-    pub fn new(env: &Env, requests_per_hour: u32) -> Result<Self> {
+impl KvBucketStore {
+    pub fn new(env: &Env) -> Result<Self> {
         let kv = env.kv("RATE_LIMIT")
             .map_err(|e| NasaApiError::Cache(format!("Failed to get rate limit KV: {e}")))?;
-        
-        Ok(Self {
-            kv,
-            requests_per_hour,
-        })
+        Ok(Self { kv })
     }
-    
-    pub async fn check_rate_limit(&self, req: &Request) -> Result<bool> {
-        let client_ip = get_client_ip(req);
-        let key = format!("rate_limit:{client_ip}");
-        let now = Utc::now();
-        
-        match self.kv.get(&key).json::<RateLimitInfo>().await {
-            Ok(Some(mut info)) => {
-                // Check if we're still in the same window
-                if now - info.window_start < Duration::hours(1) {
-                    if info.count >= self.requests_per_hour {
-                        return Ok(false); // Rate limit exceeded
-                    }
-                    info.count += 1;
-                } else {
-                    // New window
-                    info = RateLimitInfo {
-                        count: 1,
-                        window_start: now,
-                    };
-                }
-                
-                // Update the count
-                self.kv
-                    .put(&key, serde_json::to_string(&info)?)
-                    .map_err(|e| NasaApiError::Cache(format!("Failed to update rate limit: {e}")))?
-                    .expiration_ttl(3600) // 1 hour
-                    .execute()
-                    .await
-                    .map_err(|e| NasaApiError::Cache(format!("Failed to execute rate limit update: {e}")))?;
-                
-                Ok(true)
+}
+
+#[async_trait(?Send)]
+impl BucketStore for KvBucketStore {
+    async fn load(&self, key: &str) -> Result<Option<BucketState>> {
+        self.kv.get(key).json::<BucketState>().await
+            .map_err(|e| NasaApiError::Cache(format!("Failed to get rate limit: {e}")))
+    }
+
+    async fn save(&self, key: &str, state: &BucketState, ttl_seconds: u64) {
+        let result = match serde_json::to_string(state) {
+            Ok(value) => match self.kv.put(key, value) {
+                Ok(put) => put.expiration_ttl(ttl_seconds).execute().await,
+                Err(e) => Err(e),
+            },
+            Err(e) => {
+                console_error!("Failed to serialize rate limit state: {}", e);
+                return;
             }
-            Ok(None) => {
-                // First request
-                let info = RateLimitInfo {
-                    count: 1,
-                    window_start: now,
-                };
-                
-                self.kv
-                    .put(&key, serde_json::to_string(&info)?)
-                    .map_err(|e| NasaApiError::Cache(format!("Failed to set rate limit: {e}")))?
-                    .expiration_ttl(3600) // 1 hour
-                    .execute()
-                    .await
-                    .map_err(|e| NasaApiError::Cache(format!("Failed to execute rate limit set: {e}")))?;
-                
-                Ok(true)
+        };
+
+        if let Err(e) = result {
+            console_error!("Failed to update rate limit {}: {}", key, e);
+        }
+    }
+}
+
+pub struct RateLimiter<S = KvBucketStore> {
+    store: S,
+    config: RateLimitConfig,
+}
+
+impl RateLimiter {
+    pub fn new(env: &Env, config: RateLimitConfig) -> Result<Self> {
+        Ok(Self::with_store(KvBucketStore::new(env)?, config))
+    }
+}
+
+impl<S: BucketStore> RateLimiter<S> {
+    pub fn with_store(store: S, config: RateLimitConfig) -> Self {
+        Self { store, config }
+    }
+
+    /// Counts a request from `client` to `path` against every bucket it falls in.
+    ///
+    /// Returns `None` when the client is not limited at all. A bucket that can't
+    /// be read fails the whole check; one that can't be written still counts,
+    /// and the remaining buckets are checked all the same (KV allows about one
+    /// write per second per key, which bursts run into).
+    pub async fn check_rate_limit(&self, client: &Client, path: &str) -> Result<Option<RateLimitStatus>> {
+        let now = Utc::now();
+        let mut recorded = Vec::new();
+
+        for (key, quota) in self.config.buckets(client, path) {
+            let state = self.store.load(&key).await?;
+            let (state, status) = self.config.algorithm.record(state, now, quota);
+            recorded.push((key, quota, state, status));
+        }
+
+        // A request blocked by any bucket doesn't consume capacity in the others,
+        // so nothing is written unless every bucket lets it through
+        if recorded.iter().all(|(_, _, _, status)| status.allowed) {
+            for (key, quota, state, _) in &recorded {
+                self.store.save(key, state, quota.window_seconds.max(MIN_KV_TTL_SECONDS) as u64).await;
             }
-            Err(e) => Err(NasaApiError::Cache(format!("Failed to get rate limit: {e}"))),
+        }
+
+        Ok(RateLimitStatus::most_restrictive(recorded.into_iter().map(|(_, _, _, status)| status)))
+    }
+}
+
+/// Checks a request from `client` to `path` against the configured limits.
+///
+/// Returns `None` when the client is unlimited or the check itself failed; KV
+/// read problems are logged and the request is let through rather than rejected.
+pub async fn check(client: &Client, path: &str, env: &Env) -> Option<RateLimitStatus> {
+    let config = RateLimitConfig::from_env(env);
+
+    let result = match RateLimiter::new(env, config) {
+        Ok(limiter) => limiter.check_rate_limit(client, path).await,
+        Err(e) => Err(e),
+    };
+
+    result
+        .map_err(|e| console_error!("Rate limit check failed: {}", e))
+        .ok()
+        .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use futures::executor::block_on;
+
+    const QUOTA: Quota = Quota { requests: 2, window_seconds: 60 };
+    const START: i64 = 1_700_000_000;
+
+    fn run(algorithm: Algorithm, offsets: &[i64]) -> Vec<RateLimitStatus> {
+        let mut state = None;
+        offsets
+            .iter()
+            .map(|offset| {
+                let now = DateTime::from_timestamp(START + offset, 0).unwrap();
+                let (next, status) = algorithm.record(state.take(), now, QUOTA);
+                state = Some(next);
+                status
+            })
+            .collect()
+    }
+
+    fn allowed(statuses: &[RateLimitStatus]) -> Vec<bool> {
+        statuses.iter().map(|s| s.allowed).collect()
+    }
+
+    #[test]
+    fn parses_quotas() {
+        assert_eq!(Quota::parse("100/60"), Ok(Some(Quota { requests: 100, window_seconds: 60 })));
+        assert_eq!(Quota::parse("unlimited"), Ok(None));
+        assert_eq!(Quota::parse("0"), Ok(None));
+        assert!(Quota::parse("100").is_err());
+        assert!(Quota::parse("10/0").is_err());
+    }
+
+    #[test]
+    fn fixed_window_allows_a_burst_across_the_boundary() {
+        let statuses = run(Algorithm::FixedWindow, &[0, 58, 59, 60, 61]);
+
+        // Four requests pass between 58s and 61s despite a quota of two per minute
+        assert_eq!(allowed(&statuses), vec![true, true, false, true, true]);
+        assert_eq!(statuses[2].reset, START + 60);
+    }
+
+    #[test]
+    fn sliding_log_limits_any_trailing_window() {
+        let statuses = run(Algorithm::SlidingLog, &[0, 58, 59, 60, 61, 119]);
+
+        assert_eq!(allowed(&statuses), vec![true, true, false, true, false, true]);
+        assert_eq!(statuses[1].remaining, 0);
+        // Blocked at 59s: the slot taken at 0s frees up at 60s
+        assert_eq!(statuses[2].reset, START + 60);
+    }
+
+    #[test]
+    fn token_bucket_refills_gradually() {
+        // Two tokens per minute refill one token every 30s
+        let statuses = run(Algorithm::TokenBucket, &[0, 1, 2, 31, 32]);
+
+        assert_eq!(allowed(&statuses), vec![true, true, false, true, false]);
+        assert!((START + 30..=START + 31).contains(&statuses[2].reset));
+    }
+
+    #[test]
+    fn discards_state_from_another_algorithm() {
+        let now = Utc::now();
+        let fixed = BucketState::Fixed(RateLimitInfo { count: 2, window_start: now });
+
+        let (state, status) = Algorithm::TokenBucket.record(Some(fixed), now, QUOTA);
+
+        assert!(status.allowed);
+        assert!(matches!(state, BucketState::Token { .. }));
+    }
+
+    #[test]
+    fn expensive_routes_get_an_extra_bucket() {
+        let config = RateLimitConfig::default();
+        let client = Client { id: "203.0.113.7".to_string(), tier: Tier::Anonymous };
+
+        let keys: Vec<String> = config.buckets(&client, "/api/exoplanets/query").into_iter().map(|(k, _)| k).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "rate_limit:anonymous:203.0.113.7".to_string(),
+                "rate_limit:anonymous:203.0.113.7:/api/exoplanets/query".to_string(),
+            ]
+        );
+        assert_eq!(config.buckets(&client, "/api/apod").len(), 1);
+
+        let internal = Client { id: "internal".to_string(), tier: Tier::Internal };
+        assert!(config.buckets(&internal, "/api/exoplanets/query").is_empty());
+    }
+
+    #[test]
+    fn reports_the_most_restrictive_bucket() {
+        let open = RateLimitStatus { allowed: true, limit: 100, remaining: 90, reset: 10 };
+        let tight = RateLimitStatus { allowed: true, limit: 10, remaining: 2, reset: 20 };
+        let blocked = RateLimitStatus { allowed: false, limit: 10, remaining: 0, reset: 30 };
+
+        assert_eq!(RateLimitStatus::most_restrictive([open, tight]), Some(tight));
+        assert_eq!(RateLimitStatus::most_restrictive([open, blocked, tight]), Some(blocked));
+    }
+
+    /// Serves preset states and drops every write, as KV does for keys written
+    /// more than about once a second.
+    struct ReadOnlyStore(HashMap<String, BucketState>, RefCell<Vec<String>>);
+
+    #[async_trait(?Send)]
+    impl BucketStore for ReadOnlyStore {
+        async fn load(&self, key: &str) -> Result<Option<BucketState>> {
+            Ok(self.0.get(key).cloned())
+        }
+
+        async fn save(&self, key: &str, _state: &BucketState, _ttl_seconds: u64) {
+            self.1.borrow_mut().push(key.to_string());
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn failed_writes_still_check_every_bucket() {
+        let config = RateLimitConfig { algorithm: Algorithm::FixedWindow, ..RateLimitConfig::default() };
+        let client = Client { id: "203.0.113.7".to_string(), tier: Tier::Anonymous };
+        let exhausted = BucketState::Fixed(RateLimitInfo { count: 10, window_start: Utc::now() });
+        let route = "rate_limit:anonymous:203.0.113.7:/api/exoplanets/query".to_string();
+        let store = ReadOnlyStore(HashMap::from([(route, exhausted)]), RefCell::default());
+
+        let limiter = RateLimiter::with_store(store, config);
+        let status = block_on(limiter.check_rate_limit(&client, "/api/exoplanets/query")).unwrap().unwrap();
+
+        assert!(!status.allowed);
+        assert_eq!(status.limit, 10);
+        assert!(limiter.store.1.borrow().is_empty());
+    }
+
+    /// Keeps bucket states in memory.
+    #[derive(Default)]
+    struct MemoryStore(RefCell<HashMap<String, BucketState>>);
+
+    #[async_trait(?Send)]
+    impl BucketStore for MemoryStore {
+        async fn load(&self, key: &str) -> Result<Option<BucketState>> {
+            Ok(self.0.borrow().get(key).cloned())
+        }
+
+        async fn save(&self, key: &str, state: &BucketState, _ttl_seconds: u64) {
+            self.0.borrow_mut().insert(key.to_string(), state.clone());
+        }
+    }
+
+    #[test]
+    fn requests_blocked_by_the_route_bucket_leave_the_tier_bucket_alone() {
+        let config = RateLimitConfig { algorithm: Algorithm::FixedWindow, ..RateLimitConfig::default() };
+        let client = Client { id: "203.0.113.7".to_string(), tier: Tier::Anonymous };
+        let limiter = RateLimiter::with_store(MemoryStore::default(), config);
+        let check = |path| block_on(limiter.check_rate_limit(&client, path)).unwrap().unwrap();
+
+        let before = check("/api/apod");
+        let route = "rate_limit:anonymous:203.0.113.7:/api/exoplanets/query".to_string();
+        let exhausted = BucketState::Fixed(RateLimitInfo { count: 10, window_start: Utc::now() });
+        limiter.store.0.borrow_mut().insert(route, exhausted);
+
+        assert!(!check("/api/exoplanets/query").allowed);
+        assert_eq!(check("/api/apod").remaining, before.remaining - 1);
+    }
+}
@@ -0,0 +1,251 @@
+//! Per-client usage accounting, stored next to the rate limit buckets in the
+//! `RATE_LIMIT` KV namespace.
+//!
+//! Every request to a proxied endpoint adds to a daily [`DailyUsage`] record kept
+//! under `usage:<YYYY-MM-DD>:<client id>`, broken down by route pattern. Records
+//! are written after the response has been sent and expire after
+//! [`RETENTION_DAYS`]. KV is eventually consistent and offers no atomic
+//! increment, so counts from concurrent requests by the same client can be
+//! slightly low; they are meant for spotting heavy consumers, not billing.
+
+use std::collections::BTreeMap;
+
+use chrono::{Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use worker::{console_error, kv::KvStore, Env};
+
+use crate::cache::KV_LIST_PAGE;
+use crate::error::{NasaApiError, Result};
+use crate::middleware::client::{Client, Tier};
+
+/// How long daily records are kept.
+pub const RETENTION_DAYS: i64 = 30;
+
+/// What happened to a single request, as far as accounting is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    CacheHit,
+    CacheMiss,
+    /// The request failed with a server-side error, almost always upstream.
+    UpstreamError,
+    /// Rejected by the rate limiter before reaching the endpoint.
+    RateLimited,
+    /// Anything else, e.g. validation errors or uncached binary responses.
+    Other,
+}
+
+impl Outcome {
+    /// Classifies a response by its status and `X-Cache-Status` header.
+    ///
+    /// Stale responses count as cache hits, except those served because the
+    /// upstream failed, which count as upstream errors.
+    pub fn classify(status: u16, cache_status: Option<&str>) -> Self {
+        match (status, cache_status.map(str::to_ascii_uppercase).as_deref()) {
+            (429, _) => Outcome::RateLimited,
+            (500.., _) | (_, Some("STALE-ERROR")) => Outcome::UpstreamError,
+            (_, Some("HIT" | "STALE")) => Outcome::CacheHit,
+            (_, Some("MISS")) => Outcome::CacheMiss,
+            _ => Outcome::Other,
+        }
+    }
+}
+
+/// Counters for one route.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RouteUsage {
+    pub requests: u64,
+    #[serde(default)]
+    pub cache_hits: u64,
+    #[serde(default)]
+    pub cache_misses: u64,
+    #[serde(default)]
+    pub upstream_errors: u64,
+    #[serde(default)]
+    pub rate_limited: u64,
+}
+
+impl RouteUsage {
+    pub fn add(&mut self, outcome: Outcome) {
+        self.requests += 1;
+        match outcome {
+            Outcome::CacheHit => self.cache_hits += 1,
+            Outcome::CacheMiss => self.cache_misses += 1,
+            Outcome::UpstreamError => self.upstream_errors += 1,
+            Outcome::RateLimited => self.rate_limited += 1,
+            Outcome::Other => {}
+        }
+    }
+
+    fn merge(&mut self, other: &RouteUsage) {
+        self.requests += other.requests;
+        self.cache_hits += other.cache_hits;
+        self.cache_misses += other.cache_misses;
+        self.upstream_errors += other.upstream_errors;
+        self.rate_limited += other.rate_limited;
+    }
+}
+
+/// One client's usage on one UTC day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DailyUsage {
+    pub client: String,
+    pub tier: Tier,
+    pub date: NaiveDate,
+    /// Counters keyed by route pattern, e.g. `/api/mars-photos/:rover/photos`.
+    pub routes: BTreeMap<String, RouteUsage>,
+}
+
+impl DailyUsage {
+    pub fn new(client: &Client, date: NaiveDate) -> Self {
+        Self {
+            client: client.id.clone(),
+            tier: client.tier,
+            date,
+            routes: BTreeMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, route: &str, outcome: Outcome) {
+        self.routes.entry(route.to_string()).or_default().add(outcome);
+    }
+
+    /// Counters summed over every route.
+    pub fn total(&self) -> RouteUsage {
+        let mut total = RouteUsage::default();
+        for usage in self.routes.values() {
+            total.merge(usage);
+        }
+        total
+    }
+}
+
+/// KV key of the record for `client_id` on `date`.
+fn record_key(date: NaiveDate, client_id: &str) -> String {
+    format!("{}{client_id}", day_prefix(date))
+}
+
+fn day_prefix(date: NaiveDate) -> String {
+    format!("usage:{}:", date.format("%Y-%m-%d"))
+}
+
+pub struct UsageTracker {
+    kv: KvStore,
+}
+
+impl UsageTracker {
+    pub fn new(env: &Env) -> Result<Self> {
+        let kv = env.kv("RATE_LIMIT")
+            .map_err(|e| NasaApiError::Cache(format!("Failed to get usage KV: {e}")))?;
+
+        Ok(Self { kv })
+    }
+
+    /// Adds one request by `client` to `route` to today's record.
+    pub async fn record(&self, client: &Client, route: &str, outcome: Outcome) -> Result<()> {
+        let today = Utc::now().date_naive();
+        let mut usage = self
+            .get(&client.id, today)
+            .await?
+            .unwrap_or_else(|| DailyUsage::new(client, today));
+        usage.add(route, outcome);
+
+        self.kv
+            .put(&record_key(today, &client.id), serde_json::to_string(&usage)?)
+            .map_err(|e| NasaApiError::Cache(format!("Failed to update usage: {e}")))?
+            .expiration_ttl(Duration::days(RETENTION_DAYS).num_seconds() as u64)
+            .execute()
+            .await
+            .map_err(|e| NasaApiError::Cache(format!("Failed to execute usage update: {e}")))
+    }
+
+    /// Usage of one client on `date`.
+    pub async fn get(&self, client_id: &str, date: NaiveDate) -> Result<Option<DailyUsage>> {
+        self.kv
+            .get(&record_key(date, client_id))
+            .json::<DailyUsage>()
+            .await
+            .map_err(|e| NasaApiError::Cache(format!("Failed to get usage: {e}")))
+    }
+
+    /// Usage of up to `limit` clients seen on `date`, resuming from a `cursor`
+    /// returned by the previous call. The returned cursor is `None` once every
+    /// client has been read.
+    pub async fn all(
+        &self,
+        date: NaiveDate,
+        mut cursor: Option<String>,
+        limit: usize,
+    ) -> Result<(Vec<DailyUsage>, Option<String>)> {
+        let prefix = day_prefix(date);
+        let mut records = Vec::new();
+        let mut read = 0;
+
+        while read < limit {
+            let mut list = self.kv.list().prefix(prefix.clone()).limit((limit - read).min(KV_LIST_PAGE) as u64);
+            if let Some(cursor) = cursor.take() {
+                list = list.cursor(cursor);
+            }
+            let page = list
+                .execute()
+                .await
+                .map_err(|e| NasaApiError::Cache(format!("Failed to list usage: {e}")))?;
+
+            read += page.keys.len();
+            for key in &page.keys {
+                if let Some(usage) = self.get(&key.name[prefix.len()..], date).await? {
+                    records.push(usage);
+                }
+            }
+
+            match page.cursor {
+                Some(next) if !page.list_complete => cursor = Some(next),
+                _ => break,
+            }
+        }
+
+        Ok((records, cursor))
+    }
+}
+
+/// Records one request, logging rather than failing when KV is unavailable.
+pub async fn record(env: Env, client: Client, route: &'static str, outcome: Outcome) {
+    let result = match UsageTracker::new(&env) {
+        Ok(tracker) => tracker.record(&client, route, outcome).await,
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = result {
+        console_error!("Usage accounting failed: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_responses() {
+        assert_eq!(Outcome::classify(200, Some("HIT")), Outcome::CacheHit);
+        assert_eq!(Outcome::classify(200, Some("MISS")), Outcome::CacheMiss);
+        assert_eq!(Outcome::classify(200, Some("STALE")), Outcome::CacheHit);
+        assert_eq!(Outcome::classify(200, Some("STALE-ERROR")), Outcome::UpstreamError);
+        assert_eq!(Outcome::classify(200, None), Outcome::Other);
+        assert_eq!(Outcome::classify(400, None), Outcome::Other);
+        assert_eq!(Outcome::classify(429, None), Outcome::RateLimited);
+        assert_eq!(Outcome::classify(502, None), Outcome::UpstreamError);
+    }
+
+    #[test]
+    fn accumulates_per_route_and_in_total() {
+        let client = Client { id: "key:0123456789abcdef".to_string(), tier: Tier::Keyed };
+        let mut usage = DailyUsage::new(&client, NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
+
+        usage.add("/api/apod", Outcome::CacheMiss);
+        usage.add("/api/apod", Outcome::CacheHit);
+        usage.add("/api/neo/feed", Outcome::UpstreamError);
+
+        assert_eq!(usage.routes["/api/apod"], RouteUsage { requests: 2, cache_hits: 1, cache_misses: 1, ..Default::default() });
+        assert_eq!(usage.total(), RouteUsage { requests: 3, cache_hits: 1, cache_misses: 1, upstream_errors: 1, rate_limited: 0 });
+        assert_eq!(record_key(usage.date, &usage.client), "usage:2024-01-01:key:0123456789abcdef");
+    }
+}
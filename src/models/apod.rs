@@ -1,6 +1,7 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ApodResponse {
     pub copyright: Option<String>,
     pub date: String,
@@ -9,12 +10,13 @@ pub struct ApodResponse {
     pub media_type: String,
     pub service_version: String,
     pub title: String,
-    pub url: String,
+    /// Missing for some embedded media.
+    pub url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub thumbnail_url: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ApodRequest {
     pub api_key: String,
     #[serde(skip_serializing_if = "Option::is_none")]
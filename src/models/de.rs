@@ -0,0 +1,148 @@
+//! Deserializers for the upstream APIs' loosely typed values, used by the models
+//! so normalized responses carry real numbers and ISO-8601 timestamps.
+//!
+//! Fields using them are documented with [`Loose`], e.g.
+//! `#[schemars(with = "de::Loose<String, f64>")]`, so the upstream schema shows
+//! the value as sent and the normalized schema as served.
+
+use std::borrow::Cow;
+use std::marker::PhantomData;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use schemars::{JsonSchema, Schema, SchemaGenerator};
+use serde::de::{Deserializer, Error};
+use serde::Deserialize;
+
+/// Schema of a field read with one of these deserializers: `U`, the type the
+/// upstream sends, when describing what is deserialized, and `T`, the field's
+/// own type, when describing what is serialized.
+pub struct Loose<U, T>(PhantomData<(U, T)>);
+
+impl<U: JsonSchema, T: JsonSchema> JsonSchema for Loose<U, T> {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        format!("Loose_{}_{}", U::schema_name(), T::schema_name()).into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        if generator.contract().is_serialize() {
+            T::json_schema(generator)
+        } else {
+            U::json_schema(generator)
+        }
+    }
+}
+
+/// Timestamp layouts used across the NASA APIs, all in UTC.
+const TIMESTAMP_FORMATS: &[&str] = &[
+    // DONKI, e.g. 2024-01-01T12:34Z
+    "%Y-%m-%dT%H:%MZ",
+    "%Y-%m-%dT%H:%M:%SZ",
+    // EPIC and fireballs, e.g. 2024-01-01 00:13:03
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    // NeoWs and CAD, e.g. 2024-Jan-01 12:34
+    "%Y-%b-%d %H:%M",
+];
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Raw {
+    Number(f64),
+    Text(String),
+}
+
+/// Parses a timestamp in any of the upstream layouts, or a bare date as midnight.
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+    TIMESTAMP_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0))
+        .map(|naive| naive.and_utc())
+}
+
+fn to_number(raw: Raw) -> Option<f64> {
+    match raw {
+        Raw::Number(n) => Some(n),
+        Raw::Text(text) => text.trim().parse().ok(),
+    }
+}
+
+/// A number sent either as a JSON number or as a numeric string.
+pub fn number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    let raw = Raw::deserialize(deserializer)?;
+    to_number(raw).ok_or_else(|| D::Error::custom("expected a number or a numeric string"))
+}
+
+/// Like [`number`], with `null` and empty strings read as `None`.
+pub fn optional_number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+    match Option::<Raw>::deserialize(deserializer)? {
+        Some(Raw::Text(text)) if text.trim().is_empty() => Ok(None),
+        Some(raw) => to_number(raw).map(Some).ok_or_else(|| D::Error::custom("expected a number or a numeric string")),
+        None => Ok(None),
+    }
+}
+
+/// A whole number sent either as a JSON number or as a numeric string.
+pub fn integer<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    let n = number(deserializer)?;
+    if n >= 0.0 && n.fract() == 0.0 {
+        Ok(n as u64)
+    } else {
+        Err(D::Error::custom("expected a whole number"))
+    }
+}
+
+/// A timestamp in one of the upstream layouts.
+pub fn timestamp<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    parse_timestamp(&value).ok_or_else(|| D::Error::custom(format!("unrecognised timestamp {value}")))
+}
+
+/// Like [`timestamp`], with `null` and empty strings read as `None`.
+pub fn optional_timestamp<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error> {
+    match Option::<String>::deserialize(deserializer)? {
+        Some(value) if !value.trim().is_empty() => parse_timestamp(&value)
+            .map(Some)
+            .ok_or_else(|| D::Error::custom(format!("unrecognised timestamp {value}"))),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_upstream_timestamp_layouts() {
+        let expected = "2024-01-05T12:34:00Z";
+        for value in ["2024-01-05T12:34Z", "2024-01-05 12:34:00", "2024-Jan-05 12:34", "2024-01-05T12:34:00Z"] {
+            assert_eq!(parse_timestamp(value).unwrap().to_rfc3339_opts(chrono::SecondsFormat::Secs, true), expected, "{value}");
+        }
+        assert!(parse_timestamp("yesterday").is_none());
+    }
+
+    #[test]
+    fn reads_numeric_strings() {
+        #[derive(Deserialize)]
+        struct Sample {
+            #[serde(deserialize_with = "number")]
+            a: f64,
+            #[serde(deserialize_with = "optional_number")]
+            b: Option<f64>,
+            #[serde(deserialize_with = "integer")]
+            c: u64,
+        }
+
+        let sample: Sample = serde_json::from_str(r#"{"a": "12.5", "b": "", "c": "7"}"#).unwrap();
+        assert_eq!((sample.a, sample.b, sample.c), (12.5, None, 7));
+        assert!(serde_json::from_str::<Sample>(r#"{"a": "fast", "b": null, "c": 1}"#).is_err());
+    }
+}
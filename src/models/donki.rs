@@ -1,212 +1,254 @@
+//! DONKI payloads. Upstream names are camelCase (with a few irregular ones such
+//! as `activityID`); they are only used for deserialization, so normalized
+//! responses come out in snake_case.
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use super::de;
+
 // CME - Coronal Mass Ejection
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all(deserialize = "camelCase"))]
 pub struct CmeEvent {
-    #[serde(rename = "activityID")]
+    #[serde(rename(deserialize = "activityID"))]
     pub activity_id: String,
     pub catalog: String,
-    #[serde(rename = "startTime")]
-    pub start_time: String,
-    #[serde(rename = "sourceLocation")]
+    #[serde(deserialize_with = "de::timestamp")]
+    #[schemars(with = "de::Loose<String, DateTime<Utc>>")]
+    pub start_time: DateTime<Utc>,
     pub source_location: Option<String>,
-    #[serde(rename = "activeRegionNum")]
     pub active_region_num: Option<u32>,
     pub link: String,
     pub note: Option<String>,
     pub instruments: Vec<Instrument>,
-    #[serde(rename = "cmeAnalyses")]
     pub cme_analyses: Option<Vec<CmeAnalysis>>,
+    pub linked_events: Option<Vec<LinkedEvent>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all(deserialize = "camelCase"))]
 pub struct Instrument {
-    pub id: u32,
-    #[serde(rename = "displayName")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<u32>,
     pub display_name: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all(deserialize = "camelCase"))]
 pub struct CmeAnalysis {
-    pub time21_5: Option<String>,
+    #[serde(rename(deserialize = "time21_5"), default, deserialize_with = "de::optional_timestamp")]
+    #[schemars(with = "de::Loose<Option<String>, Option<DateTime<Utc>>>")]
+    pub time21_5: Option<DateTime<Utc>>,
     pub latitude: Option<f64>,
     pub longitude: Option<f64>,
     pub half_angle: Option<f64>,
     pub speed: Option<f64>,
-    #[serde(rename = "type")]
+    #[serde(rename(deserialize = "type"))]
     pub analysis_type: String,
     pub is_most_accurate: bool,
     pub note: Option<String>,
+    pub link: Option<String>,
     pub enlil_list: Option<Vec<EnlilModel>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all(deserialize = "camelCase"))]
 pub struct EnlilModel {
-    #[serde(rename = "modelCompletionTime")]
-    pub model_completion_time: Option<String>,
+    #[serde(default, deserialize_with = "de::optional_timestamp")]
+    #[schemars(with = "de::Loose<Option<String>, Option<DateTime<Utc>>>")]
+    pub model_completion_time: Option<DateTime<Utc>>,
     pub au: Option<f64>,
-    #[serde(rename = "estimatedShockArrivalTime")]
-    pub estimated_shock_arrival_time: Option<String>,
-    #[serde(rename = "estimatedDuration")]
+    #[serde(default, deserialize_with = "de::optional_timestamp")]
+    #[schemars(with = "de::Loose<Option<String>, Option<DateTime<Utc>>>")]
+    pub estimated_shock_arrival_time: Option<DateTime<Utc>>,
     pub estimated_duration: Option<f64>,
+    #[serde(rename(deserialize = "rmin_re"))]
     pub rmin_re: Option<f64>,
+    #[serde(rename(deserialize = "kp_18"))]
     pub kp_18: Option<f64>,
+    #[serde(rename(deserialize = "kp_90"))]
     pub kp_90: Option<f64>,
+    #[serde(rename(deserialize = "kp_135"))]
     pub kp_135: Option<f64>,
+    #[serde(rename(deserialize = "kp_180"))]
     pub kp_180: Option<f64>,
+    #[serde(rename(deserialize = "isEarthGB"))]
     pub is_earth_gb: bool,
     pub link: String,
     pub impact_list: Option<Vec<Impact>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all(deserialize = "camelCase"))]
 pub struct Impact {
     pub is_glancing_blow: bool,
     pub location: String,
-    pub arrival_time: String,
+    #[serde(deserialize_with = "de::timestamp")]
+    #[schemars(with = "de::Loose<String, DateTime<Utc>>")]
+    pub arrival_time: DateTime<Utc>,
 }
 
 // GST - Geomagnetic Storm
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all(deserialize = "camelCase"))]
 pub struct GstEvent {
-    #[serde(rename = "gstID")]
+    #[serde(rename(deserialize = "gstID"))]
     pub gst_id: String,
-    #[serde(rename = "startTime")]
-    pub start_time: String,
+    #[serde(deserialize_with = "de::timestamp")]
+    #[schemars(with = "de::Loose<String, DateTime<Utc>>")]
+    pub start_time: DateTime<Utc>,
     pub all_kp_index: Vec<KpIndex>,
     pub linked_events: Option<Vec<LinkedEvent>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all(deserialize = "camelCase"))]
 pub struct KpIndex {
-    #[serde(rename = "observedTime")]
-    pub observed_time: String,
-    #[serde(rename = "kpIndex")]
+    #[serde(deserialize_with = "de::timestamp")]
+    #[schemars(with = "de::Loose<String, DateTime<Utc>>")]
+    pub observed_time: DateTime<Utc>,
     pub kp_index: f64,
     pub source: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LinkedEvent {
-    #[serde(rename = "activityID")]
+    #[serde(rename(deserialize = "activityID"))]
     pub activity_id: String,
 }
 
 // FLR - Solar Flare
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all(deserialize = "camelCase"))]
 pub struct FlrEvent {
-    #[serde(rename = "flrID")]
+    #[serde(rename(deserialize = "flrID"))]
     pub flr_id: String,
     pub instruments: Vec<Instrument>,
-    #[serde(rename = "beginTime")]
-    pub begin_time: String,
-    #[serde(rename = "peakTime")]
-    pub peak_time: Option<String>,
-    #[serde(rename = "endTime")]
-    pub end_time: Option<String>,
-    #[serde(rename = "classType")]
+    #[serde(deserialize_with = "de::timestamp")]
+    #[schemars(with = "de::Loose<String, DateTime<Utc>>")]
+    pub begin_time: DateTime<Utc>,
+    #[serde(default, deserialize_with = "de::optional_timestamp")]
+    #[schemars(with = "de::Loose<Option<String>, Option<DateTime<Utc>>>")]
+    pub peak_time: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "de::optional_timestamp")]
+    #[schemars(with = "de::Loose<Option<String>, Option<DateTime<Utc>>>")]
+    pub end_time: Option<DateTime<Utc>>,
     pub class_type: String,
-    #[serde(rename = "sourceLocation")]
     pub source_location: Option<String>,
-    #[serde(rename = "activeRegionNum")]
     pub active_region_num: Option<u32>,
     pub linked_events: Option<Vec<LinkedEvent>>,
 }
 
 // SEP - Solar Energetic Particle
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all(deserialize = "camelCase"))]
 pub struct SepEvent {
-    #[serde(rename = "sepID")]
+    #[serde(rename(deserialize = "sepID"))]
     pub sep_id: String,
-    #[serde(rename = "eventTime")]
-    pub event_time: String,
+    #[serde(deserialize_with = "de::timestamp")]
+    #[schemars(with = "de::Loose<String, DateTime<Utc>>")]
+    pub event_time: DateTime<Utc>,
     pub instruments: Vec<Instrument>,
     pub linked_events: Option<Vec<LinkedEvent>>,
 }
 
 // IPS - Interplanetary Shock
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all(deserialize = "camelCase"))]
 pub struct IpsEvent {
     pub catalog: String,
-    #[serde(rename = "activityID")]
+    #[serde(rename(deserialize = "activityID"))]
     pub activity_id: String,
     pub location: String,
-    #[serde(rename = "eventTime")]
-    pub event_time: String,
+    #[serde(deserialize_with = "de::timestamp")]
+    #[schemars(with = "de::Loose<String, DateTime<Utc>>")]
+    pub event_time: DateTime<Utc>,
     pub link: Option<String>,
     pub instruments: Vec<Instrument>,
 }
 
 // MPC - Magnetopause Crossing
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all(deserialize = "camelCase"))]
 pub struct MpcEvent {
-    #[serde(rename = "mpcID")]
+    #[serde(rename(deserialize = "mpcID"))]
     pub mpc_id: String,
-    #[serde(rename = "eventTime")]
-    pub event_time: String,
+    #[serde(deserialize_with = "de::timestamp")]
+    #[schemars(with = "de::Loose<String, DateTime<Utc>>")]
+    pub event_time: DateTime<Utc>,
 }
 
 // RBE - Radiation Belt Enhancement
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all(deserialize = "camelCase"))]
 pub struct RbeEvent {
-    #[serde(rename = "rbeID")]
+    #[serde(rename(deserialize = "rbeID"))]
     pub rbe_id: String,
-    #[serde(rename = "eventTime")]
-    pub event_time: String,
+    #[serde(deserialize_with = "de::timestamp")]
+    #[schemars(with = "de::Loose<String, DateTime<Utc>>")]
+    pub event_time: DateTime<Utc>,
     pub instruments: Vec<Instrument>,
 }
 
 // HSS - High Speed Stream
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all(deserialize = "camelCase"))]
 pub struct HssEvent {
-    #[serde(rename = "hssID")]
+    #[serde(rename(deserialize = "hssID"))]
     pub hss_id: String,
-    #[serde(rename = "eventTime")]
-    pub event_time: String,
+    #[serde(deserialize_with = "de::timestamp")]
+    #[schemars(with = "de::Loose<String, DateTime<Utc>>")]
+    pub event_time: DateTime<Utc>,
     pub instruments: Vec<Instrument>,
     pub link: Option<String>,
 }
 
 // WSA+Enlil Simulation
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all(deserialize = "camelCase"))]
 pub struct WsaEnlilSimulation {
-    #[serde(rename = "simulationID")]
+    #[serde(rename(deserialize = "simulationID"))]
     pub simulation_id: String,
-    #[serde(rename = "modelCompletionTime")]
-    pub model_completion_time: String,
+    #[serde(deserialize_with = "de::timestamp")]
+    #[schemars(with = "de::Loose<String, DateTime<Utc>>")]
+    pub model_completion_time: DateTime<Utc>,
     pub au: Option<f64>,
-    #[serde(rename = "estimatedShockArrivalTime")]
-    pub estimated_shock_arrival_time: Option<String>,
-    #[serde(rename = "estimatedDuration")]
+    #[serde(default, deserialize_with = "de::optional_timestamp")]
+    #[schemars(with = "de::Loose<Option<String>, Option<DateTime<Utc>>>")]
+    pub estimated_shock_arrival_time: Option<DateTime<Utc>>,
     pub estimated_duration: Option<f64>,
-    pub earth_gb: Option<bool>,
+    #[serde(rename(deserialize = "isEarthGB"))]
+    pub is_earth_gb: Option<bool>,
     pub link: String,
     pub impact_list: Option<Vec<Impact>>,
-    #[serde(rename = "cmeInputs")]
     pub cme_inputs: Option<Vec<CmeInput>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all(deserialize = "camelCase"))]
 pub struct CmeInput {
-    #[serde(rename = "cmeStartTime")]
-    pub cme_start_time: String,
-    pub latitude: f64,
-    pub longitude: f64,
-    pub speed: f64,
-    pub half_angle: f64,
+    #[serde(deserialize_with = "de::timestamp")]
+    #[schemars(with = "de::Loose<String, DateTime<Utc>>")]
+    pub cme_start_time: DateTime<Utc>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub speed: Option<f64>,
+    pub half_angle: Option<f64>,
 }
 
 // Notification
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all(deserialize = "camelCase"))]
 pub struct Notification {
-    #[serde(rename = "messageType")]
     pub message_type: String,
-    #[serde(rename = "messageID")]
+    #[serde(rename(deserialize = "messageID"))]
     pub message_id: String,
-    #[serde(rename = "messageURL")]
+    #[serde(rename(deserialize = "messageURL"))]
     pub message_url: String,
-    #[serde(rename = "messageIssueTime")]
-    pub message_issue_time: String,
-    #[serde(rename = "messageBody")]
+    #[serde(deserialize_with = "de::timestamp")]
+    #[schemars(with = "de::Loose<String, DateTime<Utc>>")]
+    pub message_issue_time: DateTime<Utc>,
     pub message_body: String,
-}
\ No newline at end of file
+}
@@ -1,6 +1,7 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct EarthImageryRequest {
     pub lat: f64,
     pub lon: f64,
@@ -11,7 +12,7 @@ pub struct EarthImageryRequest {
     pub api_key: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct EarthAssetsRequest {
     pub lat: f64,
     pub lon: f64,
@@ -21,7 +22,7 @@ pub struct EarthAssetsRequest {
     pub api_key: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct EarthAsset {
     pub date: String,
     pub id: String,
@@ -1,6 +1,10 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use super::de;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct EpicImage {
     pub identifier: String,
     pub caption: String,
@@ -11,24 +15,26 @@ pub struct EpicImage {
     pub lunar_j2000_position: Position,
     pub sun_j2000_position: Position,
     pub attitude_quaternions: Quaternions,
-    pub date: String,
+    #[serde(deserialize_with = "de::timestamp")]
+    #[schemars(with = "de::Loose<String, DateTime<Utc>>")]
+    pub date: DateTime<Utc>,
     pub coords: ImageCoords,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Coordinates {
     pub lat: f64,
     pub lon: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Position {
     pub x: f64,
     pub y: f64,
     pub z: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Quaternions {
     pub q0: f64,
     pub q1: f64,
@@ -36,7 +42,7 @@ pub struct Quaternions {
     pub q3: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ImageCoords {
     pub centroid_coordinates: Coordinates,
     pub dscovr_j2000_position: Position,
@@ -45,7 +51,7 @@ pub struct ImageCoords {
     pub attitude_quaternions: Quaternions,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct EpicDate {
     pub date: String,
 }
\ No newline at end of file
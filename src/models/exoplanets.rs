@@ -1,20 +1,21 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ExoplanetQueryRequest {
     pub query: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub format: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ExoplanetResponse {
     pub data: Vec<HashMap<String, serde_json::Value>>,
 }
 
 // Common exoplanet fields for typed access
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Exoplanet {
     pub pl_name: Option<String>,         // Planet name
     pub hostname: Option<String>,        // Host star name
@@ -1,11 +1,14 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MarsPhotosResponse {
+    /// `latest_photos` upstream for the latest-sol route.
+    #[serde(alias = "latest_photos")]
     pub photos: Vec<MarsPhoto>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MarsPhoto {
     pub id: u32,
     pub sol: u32,
@@ -15,7 +18,7 @@ pub struct MarsPhoto {
     pub rover: Rover,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Camera {
     pub id: u32,
     pub name: String,
@@ -23,7 +26,7 @@ pub struct Camera {
     pub full_name: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Rover {
     pub id: u32,
     pub name: String,
@@ -36,18 +39,18 @@ pub struct Rover {
     pub cameras: Vec<CameraInfo>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CameraInfo {
     pub name: String,
     pub full_name: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MarsManifest {
     pub photo_manifest: PhotoManifest,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PhotoManifest {
     pub name: String,
     pub landing_date: String,
@@ -59,7 +62,7 @@ pub struct PhotoManifest {
     pub photos: Vec<SolSummary>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SolSummary {
     pub sol: u32,
     pub earth_date: String,
@@ -67,7 +70,7 @@ pub struct SolSummary {
     pub cameras: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MarsPhotoRequest {
     pub rover: String,
     pub api_key: String,
@@ -1,11 +1,12 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MediaSearchResponse {
     pub collection: MediaCollection,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MediaCollection {
     pub version: String,
     pub href: String,
@@ -14,14 +15,14 @@ pub struct MediaCollection {
     pub links: Vec<MediaLink>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MediaItem {
     pub href: String,
     pub data: Vec<MediaData>,
     pub links: Vec<MediaLink>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MediaData {
     pub center: Option<String>,
     pub title: String,
@@ -36,7 +37,7 @@ pub struct MediaData {
     pub album: Option<Vec<String>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MediaLink {
     pub rel: String,
     pub prompt: Option<String>,
@@ -44,29 +45,35 @@ pub struct MediaLink {
     pub render: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MediaMetadata {
     pub total_hits: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Metadata and captions lookups answer with the URL of the file.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MediaLocation {
+    pub location: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MediaAssetResponse {
     pub collection: AssetCollection,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AssetCollection {
     pub version: String,
     pub href: String,
     pub items: Vec<AssetItem>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AssetItem {
     pub href: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MediaSearchRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub q: Option<String>,
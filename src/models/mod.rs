@@ -1,6 +1,7 @@
 #![allow(dead_code)]
 
 pub mod apod;
+pub mod de;
 pub mod neo;
 pub mod donki;
 pub mod earth;
@@ -12,16 +13,21 @@ pub mod exoplanets;
 pub mod ssd;
 
 // Common types used across multiple APIs
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Envelope of every `/api/v2` response.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(inline)]
 pub struct ApiResponse<T> {
     pub data: T,
     pub status: String,
     pub cached: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// One page of a list, as `/api/v2` serves paged lists.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(inline)]
 pub struct PaginatedResponse<T> {
     pub items: Vec<T>,
     pub page: u32,
@@ -29,7 +35,7 @@ pub struct PaginatedResponse<T> {
     pub total_items: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DateRange {
     pub start_date: String,
     pub end_date: String,
@@ -1,14 +1,19 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use super::{de, PaginatedResponse};
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct NeoFeedResponse {
     pub links: Links,
     pub element_count: u32,
-    pub near_earth_objects: HashMap<String, Vec<Neo>>,
+    /// Objects keyed by close-approach date, in date order.
+    pub near_earth_objects: BTreeMap<String, Vec<Neo>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Neo {
     pub id: String,
     pub neo_reference_id: String,
@@ -21,7 +26,7 @@ pub struct Neo {
     pub is_sentry_object: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct EstimatedDiameter {
     pub kilometers: DiameterRange,
     pub meters: DiameterRange,
@@ -29,38 +34,54 @@ pub struct EstimatedDiameter {
     pub feet: DiameterRange,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DiameterRange {
     pub estimated_diameter_min: f64,
     pub estimated_diameter_max: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CloseApproach {
     pub close_approach_date: String,
-    pub close_approach_date_full: String,
+    #[serde(default, deserialize_with = "de::optional_timestamp")]
+    #[schemars(with = "de::Loose<Option<String>, Option<DateTime<Utc>>>")]
+    pub close_approach_date_full: Option<DateTime<Utc>>,
     pub epoch_date_close_approach: i64,
     pub relative_velocity: VelocityData,
     pub miss_distance: DistanceData,
     pub orbiting_body: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct VelocityData {
-    pub kilometers_per_second: String,
-    pub kilometers_per_hour: String,
-    pub miles_per_hour: String,
+    #[serde(deserialize_with = "de::number")]
+    #[schemars(with = "de::Loose<String, f64>")]
+    pub kilometers_per_second: f64,
+    #[serde(deserialize_with = "de::number")]
+    #[schemars(with = "de::Loose<String, f64>")]
+    pub kilometers_per_hour: f64,
+    #[serde(deserialize_with = "de::number")]
+    #[schemars(with = "de::Loose<String, f64>")]
+    pub miles_per_hour: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DistanceData {
-    pub astronomical: String,
-    pub lunar: String,
-    pub kilometers: String,
-    pub miles: String,
+    #[serde(deserialize_with = "de::number")]
+    #[schemars(with = "de::Loose<String, f64>")]
+    pub astronomical: f64,
+    #[serde(deserialize_with = "de::number")]
+    #[schemars(with = "de::Loose<String, f64>")]
+    pub lunar: f64,
+    #[serde(deserialize_with = "de::number")]
+    #[schemars(with = "de::Loose<String, f64>")]
+    pub kilometers: f64,
+    #[serde(deserialize_with = "de::number")]
+    #[schemars(with = "de::Loose<String, f64>")]
+    pub miles: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Links {
     pub next: Option<String>,
     pub prev: Option<String>,
@@ -68,14 +89,25 @@ pub struct Links {
     pub self_link: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct NeoBrowseResponse {
     pub links: Links,
     pub page: PageInfo,
     pub near_earth_objects: Vec<Neo>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl From<NeoBrowseResponse> for PaginatedResponse<Neo> {
+    fn from(browse: NeoBrowseResponse) -> Self {
+        Self {
+            items: browse.near_earth_objects,
+            page: browse.page.number,
+            total_pages: browse.page.total_pages,
+            total_items: browse.page.total_elements,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PageInfo {
     pub size: u32,
     pub total_elements: u32,
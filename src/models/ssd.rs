@@ -1,21 +1,30 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use super::de;
+
 // CAD - Close Approach Data
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CadResponse {
     pub signature: CadSignature,
-    pub count: String,
+    #[serde(deserialize_with = "de::integer")]
+    #[schemars(with = "de::Loose<String, u64>")]
+    pub count: u64,
+    /// Column names; absent when nothing matched.
+    #[serde(default)]
     pub fields: Vec<String>,
-    pub data: Vec<Vec<String>>,
+    /// One row per close approach, in the order of `fields`.
+    #[serde(default)]
+    pub data: Vec<Vec<Option<String>>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CadSignature {
     pub source: String,
     pub version: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CadRequest {
     #[serde(rename = "date-min", skip_serializing_if = "Option::is_none")]
     pub date_min: Option<String>,
@@ -50,7 +59,7 @@ pub struct CadRequest {
 }
 
 // SBDB - Small Body Database
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SbdbResponse {
     pub signature: SbdbSignature,
     pub object: SbdbObject,
@@ -62,13 +71,13 @@ pub struct SbdbResponse {
     pub discovery: Option<DiscoveryData>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SbdbSignature {
     pub source: String,
     pub version: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SbdbObject {
     pub des: String,
     pub name: Option<String>,
@@ -79,7 +88,7 @@ pub struct SbdbObject {
     pub orbit: OrbitData,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct OrbitData {
     pub source: String,
     pub cov_epoch: Option<String>,
@@ -92,7 +101,7 @@ pub struct OrbitData {
     pub orbit: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CloseApproachData {
     pub cd: String,
     pub dist: String,
@@ -104,7 +113,7 @@ pub struct CloseApproachData {
     pub body: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PhysicalParameter {
     pub name: String,
     pub value: String,
@@ -114,7 +123,7 @@ pub struct PhysicalParameter {
     pub notes: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DiscoveryData {
     pub date: String,
     pub site: String,
@@ -122,20 +131,20 @@ pub struct DiscoveryData {
 }
 
 // Sentry - Impact Risk Assessment
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SentryResponse {
     pub signature: SentrySignature,
     pub count: String,
     pub data: Vec<SentryObject>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SentrySignature {
     pub source: String,
     pub version: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SentryObject {
     pub des: String,
     pub name: Option<String>,
@@ -158,35 +167,41 @@ pub struct SentryObject {
 }
 
 // Fireball
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FireballResponse {
     pub signature: FireballSignature,
-    pub count: String,
+    #[serde(deserialize_with = "de::integer")]
+    #[schemars(with = "de::Loose<String, u64>")]
+    pub count: u64,
+    /// Column names; absent when nothing matched.
+    #[serde(default)]
     pub fields: Vec<String>,
+    /// One row per fireball, in the order of `fields`.
+    #[serde(default)]
     pub data: Vec<Vec<Option<String>>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FireballSignature {
     pub source: String,
     pub version: String,
 }
 
 // NHATS - Near-Earth Object Human Space Flight Accessible Targets Study
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct NhatsResponse {
     pub signature: NhatsSignature,
     pub count: String,
     pub data: Vec<NhatsObject>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct NhatsSignature {
     pub source: String,
     pub version: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct NhatsObject {
     pub des: String,
     pub fullname: String,
@@ -196,7 +211,7 @@ pub struct NhatsObject {
     pub viable: Vec<ViableTrajectory>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ViableTrajectory {
     pub dv_total: String,
     pub dur_total: String,
@@ -209,19 +224,19 @@ pub struct ViableTrajectory {
 }
 
 // Scout
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ScoutResponse {
     pub signature: ScoutSignature,
     pub object: ScoutObject,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ScoutSignature {
     pub source: String,
     pub version: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ScoutObject {
     pub tdes: String,
     pub nobs: u32,
@@ -235,7 +250,7 @@ pub struct ScoutObject {
     pub observations: Vec<Observation>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Observation {
     pub site: String,
     pub time: String,
@@ -1,6 +1,7 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TechTransferResponse {
     pub results: Vec<TechItem>,
     pub count: u32,
@@ -9,7 +10,7 @@ pub struct TechTransferResponse {
     pub page: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TechItem {
     #[serde(rename = "id")]
     pub id: String,
@@ -33,7 +34,7 @@ pub struct TechItem {
     pub concepts: Option<Vec<String>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Patent {
     #[serde(rename = "id")]
     pub id: String,
@@ -49,7 +50,7 @@ pub struct Patent {
     pub item: TechItem,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Software {
     #[serde(rename = "id")]
     pub id: String,
@@ -65,7 +66,7 @@ pub struct Software {
     pub item: TechItem,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Spinoff {
     #[serde(rename = "id")]
     pub id: String,
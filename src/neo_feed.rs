@@ -0,0 +1,424 @@
+//! Worker-side refinement of the NeoWs feed (`/api/neo/feed`): filtering by
+//! hazard, size and miss distance, sorting and flattening across dates.
+//!
+//! Refinements are applied to the cached payload, raw or normalized, so every
+//! variation of a date range shares one cache entry. Measurements come from an
+//! object's first close approach and its maximum estimated diameter, as in the
+//! CLI's `asteroids feed-extended`. Objects without the measurement a filter
+//! needs are kept, and sort last.
+//!
+//! NeoWs serves at most [`WINDOW_DAYS`] per call, so longer ranges are split
+//! into consecutive windows by [`chunks`], fetched and cached one by one, and
+//! put back together by [`merge`] before anything is refined.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use chrono::{Duration, NaiveDate};
+use serde_json::{json, Value};
+
+use crate::error::Problem;
+use crate::registry::ProxyRequest;
+
+/// Days `end_date` may be after `start_date` in one NeoWs feed call.
+pub const WINDOW_DAYS: i64 = 7;
+
+/// Days a feed may span unless `NEO_FEED_MAX_DAYS` says otherwise.
+pub const MAX_DAYS: i64 = 31;
+
+/// Most windows one feed request is split into. A window costs up to eight
+/// subrequests (cache read, fill lock read, write and delete, an upstream fetch
+/// with two retries, cache write), so five of them plus the client and rate
+/// limit lookups fit the 50 subrequests a Worker may make per request on the
+/// free plan. That leaves nothing for waiting on another isolate's fill, so
+/// windows never do: one locked by another isolate is fetched again.
+const MAX_WINDOWS: i64 = 5;
+
+/// Ceiling for `NEO_FEED_MAX_DAYS`: the span covered by [`MAX_WINDOWS`] windows.
+pub const MAX_DAYS_CEILING: i64 = MAX_WINDOWS * (WINDOW_DAYS + 1) - 1;
+
+/// Size classes of the `size` filter, by maximum estimated diameter.
+pub const SIZES: &[&str] = &["small", "medium", "large"];
+
+/// Values of `sort`; a leading `-` sorts in descending order.
+pub const SORT_KEYS: &[&str] = &[
+    "miss_distance",
+    "-miss_distance",
+    "velocity",
+    "-velocity",
+    "diameter",
+    "-diameter",
+    "approach_time",
+    "-approach_time",
+];
+
+/// A number sent as either a JSON number or a numeric string (NeoWs sends both).
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn first_approach(neo: &Value) -> Option<&Value> {
+    neo.get("close_approach_data")?.get(0)
+}
+
+/// Miss distance in astronomical units.
+fn miss_distance(neo: &Value) -> Option<f64> {
+    number(first_approach(neo)?.get("miss_distance")?.get("astronomical")?)
+}
+
+/// Relative velocity in km/s.
+fn velocity(neo: &Value) -> Option<f64> {
+    number(first_approach(neo)?.get("relative_velocity")?.get("kilometers_per_second")?)
+}
+
+/// Maximum estimated diameter in kilometres.
+fn diameter(neo: &Value) -> Option<f64> {
+    number(neo.get("estimated_diameter")?.get("kilometers")?.get("estimated_diameter_max")?)
+}
+
+/// Close approach time in milliseconds since the epoch.
+fn approach_time(neo: &Value) -> Option<f64> {
+    number(first_approach(neo)?.get("epoch_date_close_approach")?)
+}
+
+fn date(request: &ProxyRequest, name: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(request.param(name)?, "%Y-%m-%d").ok()
+}
+
+/// `request` restricted to the dates from `start` to `end`.
+fn window(request: &ProxyRequest, start: NaiveDate, end: NaiveDate) -> ProxyRequest {
+    let mut window = request.clone();
+    for (name, value) in &mut window.query {
+        match name.as_str() {
+            "start_date" => *value = start.format("%Y-%m-%d").to_string(),
+            "end_date" => *value = end.format("%Y-%m-%d").to_string(),
+            _ => {}
+        }
+    }
+    window
+}
+
+/// Reports a feed whose `end_date` is more than `max_days` after `start_date`.
+pub fn check_range(request: &ProxyRequest, max_days: i64, problems: &mut Vec<Problem>) {
+    if let (Some(start), Some(end)) = (date(request, "start_date"), date(request, "end_date")) {
+        if (end - start).num_days() > max_days {
+            problems.push(Problem::new("end_date", format!("must be at most {max_days} days after start_date")));
+        }
+    }
+}
+
+/// Splits a checked feed request into calls of at most [`WINDOW_DAYS`] each,
+/// starting at `start_date`. Requests without both dates, or within one window,
+/// are left whole.
+pub fn chunks(request: &ProxyRequest) -> Vec<ProxyRequest> {
+    let (Some(start), Some(end)) = (date(request, "start_date"), date(request, "end_date")) else {
+        return vec![request.clone()];
+    };
+    if (end - start).num_days() <= WINDOW_DAYS {
+        return vec![request.clone()];
+    }
+
+    let mut chunks = Vec::new();
+    let mut from = start;
+    while from <= end {
+        let to = (from + Duration::days(WINDOW_DAYS)).min(end);
+        chunks.push(window(request, from, to));
+        from = to + Duration::days(1);
+    }
+    chunks
+}
+
+/// Puts the feeds of consecutive windows back together: `near_earth_objects`
+/// holds every date and `element_count` counts them all. `links` are the first
+/// window's, with `next` pointing past the last one.
+pub fn merge(feeds: Vec<Value>) -> Value {
+    let mut feeds = feeds.into_iter();
+    let Some(mut merged) = feeds.next() else {
+        return Value::Null;
+    };
+
+    for mut feed in feeds {
+        let more = feed.get_mut("near_earth_objects").map(Value::take);
+        if let (Some(Value::Object(dates)), Some(Value::Object(more))) = (merged.get_mut("near_earth_objects"), more) {
+            dates.extend(more);
+        }
+        let next = feed.pointer_mut("/links/next").map(Value::take);
+        if let (Some(Value::Object(links)), Some(next)) = (merged.get_mut("links"), next) {
+            links.insert("next".to_string(), next);
+        }
+    }
+
+    let count: usize = merged
+        .get("near_earth_objects")
+        .and_then(Value::as_object)
+        .map(|dates| dates.values().filter_map(Value::as_array).map(Vec::len).sum())
+        .unwrap_or_default();
+    if let Value::Object(feed) = &mut merged {
+        feed.insert("element_count".to_string(), json!(count));
+    }
+    merged
+}
+
+/// Which objects of the feed to keep.
+struct Filter<'a> {
+    hazardous: bool,
+    size: Option<&'a str>,
+    min_distance: Option<f64>,
+    max_distance: Option<f64>,
+}
+
+impl<'a> Filter<'a> {
+    fn new(request: &'a ProxyRequest) -> Self {
+        let distance = |name| request.option(name).and_then(|value| value.parse().ok());
+        Self {
+            hazardous: request.option("hazardous") == Some("true"),
+            size: request.option("size"),
+            min_distance: distance("min_distance"),
+            max_distance: distance("max_distance"),
+        }
+    }
+
+    fn keeps(&self, neo: &Value) -> bool {
+        if self.hazardous && neo.get("is_potentially_hazardous_asteroid").and_then(Value::as_bool) == Some(false) {
+            return false;
+        }
+        if let (Some(size), Some(diameter)) = (self.size, diameter(neo)) {
+            let matches = match size {
+                "small" => diameter < 0.1,
+                "medium" => (0.1..1.0).contains(&diameter),
+                "large" => diameter >= 1.0,
+                _ => true,
+            };
+            if !matches {
+                return false;
+            }
+        }
+        match miss_distance(neo) {
+            Some(distance) => {
+                self.min_distance.is_none_or(|min| distance >= min) && self.max_distance.is_none_or(|max| distance <= max)
+            }
+            None => true,
+        }
+    }
+}
+
+/// Sorts `neos` by a [`SORT_KEYS`] value, keeping the upstream order for ties.
+fn sort(neos: &mut [Value], sort: &str) {
+    let (key, descending) = match sort.strip_prefix('-') {
+        Some(key) => (key, true),
+        None => (sort, false),
+    };
+    let measure: fn(&Value) -> Option<f64> = match key {
+        "miss_distance" => miss_distance,
+        "velocity" => velocity,
+        "diameter" => diameter,
+        "approach_time" => approach_time,
+        _ => return,
+    };
+    neos.sort_by(|a, b| match (measure(a), measure(b)) {
+        (Some(a), Some(b)) if descending => b.total_cmp(&a),
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    });
+}
+
+/// Applies the request's filters and sort to a feed and recomputes
+/// `element_count`. With `flatten=true`, `near_earth_objects` becomes one list
+/// across all dates (in date order before sorting) instead of a map by date.
+pub fn refine(request: &ProxyRequest, mut feed: Value) -> Value {
+    let Some(Value::Object(dates)) = feed.get_mut("near_earth_objects").map(Value::take) else {
+        return feed;
+    };
+
+    let filter = Filter::new(request);
+    let mut dates: BTreeMap<String, Vec<Value>> = dates
+        .into_iter()
+        .map(|(date, neos)| {
+            let neos = match neos {
+                Value::Array(neos) => neos.into_iter().filter(|neo| filter.keeps(neo)).collect(),
+                _ => Vec::new(),
+            };
+            (date, neos)
+        })
+        .collect();
+    let count: usize = dates.values().map(Vec::len).sum();
+
+    feed["near_earth_objects"] = if request.option("flatten") == Some("true") {
+        let mut neos: Vec<Value> = dates.into_values().flatten().collect();
+        if let Some(key) = request.option("sort") {
+            sort(&mut neos, key);
+        }
+        json!(neos)
+    } else {
+        if let Some(key) = request.option("sort") {
+            dates.values_mut().for_each(|neos| sort(neos, key));
+        }
+        json!(dates)
+    };
+    feed["element_count"] = json!(count);
+    feed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry;
+
+    fn neo(id: &str, hazardous: bool, diameter: f64, distance: &str, velocity: f64) -> Value {
+        json!({
+            "id": id,
+            "is_potentially_hazardous_asteroid": hazardous,
+            "estimated_diameter": {"kilometers": {"estimated_diameter_min": diameter / 2.0, "estimated_diameter_max": diameter}},
+            "close_approach_data": [{
+                "epoch_date_close_approach": 1704067200000_i64,
+                "miss_distance": {"astronomical": distance},
+                "relative_velocity": {"kilometers_per_second": velocity.to_string()}
+            }]
+        })
+    }
+
+    fn feed() -> Value {
+        json!({
+            "links": {"self": "https://api.nasa.gov/neo/rest/v1/feed"},
+            "element_count": 4,
+            "near_earth_objects": {
+                "2024-01-02": [neo("a", true, 1.5, "0.30", 20.0), neo("b", false, 0.05, "0.01", 5.0)],
+                "2024-01-01": [neo("c", true, 0.5, "0.02", 12.0), neo("d", false, 0.2, "0.40", 30.0)]
+            }
+        })
+    }
+
+    fn refined(query: &[(&str, &str)]) -> Value {
+        let endpoint = registry::find("/api/neo/feed").unwrap();
+        let query = query.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        refine(&ProxyRequest::new(endpoint, Default::default(), query), feed())
+    }
+
+    fn feed_request(query: &[(&str, &str)]) -> ProxyRequest {
+        let endpoint = registry::find("/api/neo/feed").unwrap();
+        ProxyRequest::new(endpoint, Default::default(), query.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect())
+    }
+
+    fn windows(chunks: &[ProxyRequest]) -> Vec<(&str, &str)> {
+        chunks.iter().map(|chunk| (chunk.param("start_date").unwrap(), chunk.param("end_date").unwrap())).collect()
+    }
+
+    fn ids(neos: &Value) -> Vec<&str> {
+        neos.as_array().unwrap().iter().map(|neo| neo["id"].as_str().unwrap()).collect()
+    }
+
+    #[test]
+    fn splits_long_ranges_into_windows() {
+        let request = feed_request(&[("start_date", "2024-01-01"), ("end_date", "2024-01-31"), ("sort", "velocity")]);
+
+        let chunks = chunks(&request);
+
+        assert_eq!(
+            windows(&chunks),
+            [("2024-01-01", "2024-01-08"), ("2024-01-09", "2024-01-16"), ("2024-01-17", "2024-01-24"), ("2024-01-25", "2024-01-31")]
+        );
+        assert!(chunks.iter().all(|chunk| chunk.option("sort") == Some("velocity")));
+    }
+
+    #[test]
+    fn leaves_short_and_open_ranges_whole() {
+        let week = feed_request(&[("start_date", "2024-01-01"), ("end_date", "2024-01-08")]);
+        let open = feed_request(&[("start_date", "2024-01-01")]);
+
+        assert_eq!(chunks(&week)[0].query, week.query);
+        assert_eq!(chunks(&open).len(), 1);
+    }
+
+    #[test]
+    fn rejects_ranges_over_the_limit() {
+        let request = feed_request(&[("start_date", "2024-01-01"), ("end_date", "2024-02-02")]);
+        let problems = |max_days| {
+            let mut problems = Vec::new();
+            check_range(&request, max_days, &mut problems);
+            problems.iter().map(Problem::to_string).collect::<Vec<_>>()
+        };
+
+        assert_eq!(problems(MAX_DAYS), ["end_date must be at most 31 days after start_date"]);
+        assert!(problems(MAX_DAYS_CEILING).is_empty());
+        assert_eq!(chunks(&request).len(), 5);
+    }
+
+    #[test]
+    fn the_ceiling_is_reached_in_the_most_windows() {
+        let end = (NaiveDate::from_ymd_opt(2024, 1, 1).unwrap() + Duration::days(MAX_DAYS_CEILING)).format("%Y-%m-%d").to_string();
+        let request = feed_request(&[("start_date", "2024-01-01"), ("end_date", &end)]);
+
+        assert_eq!(chunks(&request).len() as i64, MAX_WINDOWS);
+    }
+
+    #[test]
+    fn merges_windows_and_recounts() {
+        let first = json!({
+            "links": {"next": "https://api.nasa.gov/neo/rest/v1/feed?start_date=2024-01-02", "self": "https://api.nasa.gov/neo/rest/v1/feed?start_date=2024-01-01"},
+            "element_count": 1,
+            "near_earth_objects": {"2024-01-01": [neo("a", true, 1.5, "0.30", 20.0)]}
+        });
+        let second = json!({
+            "links": {"next": "https://api.nasa.gov/neo/rest/v1/feed?start_date=2024-01-03", "self": "https://api.nasa.gov/neo/rest/v1/feed?start_date=2024-01-02"},
+            "element_count": 2,
+            "near_earth_objects": {"2024-01-02": [neo("b", false, 0.05, "0.01", 5.0), neo("c", true, 0.5, "0.02", 12.0)]}
+        });
+
+        let merged = merge(vec![first, second]);
+
+        assert_eq!(merged["element_count"], 3);
+        assert_eq!(ids(&merged["near_earth_objects"]["2024-01-01"]), ["a"]);
+        assert_eq!(ids(&merged["near_earth_objects"]["2024-01-02"]), ["b", "c"]);
+        assert_eq!(merged["links"]["next"], "https://api.nasa.gov/neo/rest/v1/feed?start_date=2024-01-03");
+        assert_eq!(merged["links"]["self"], "https://api.nasa.gov/neo/rest/v1/feed?start_date=2024-01-01");
+    }
+
+    #[test]
+    fn keeps_everything_without_refinements() {
+        assert_eq!(refined(&[("start_date", "2024-01-01")]), feed());
+    }
+
+    #[test]
+    fn filters_and_recounts() {
+        let feed = refined(&[("hazardous", "true"), ("max_distance", "0.1")]);
+
+        assert_eq!(feed["element_count"], 1);
+        assert_eq!(ids(&feed["near_earth_objects"]["2024-01-01"]), ["c"]);
+        assert_eq!(ids(&feed["near_earth_objects"]["2024-01-02"]), Vec::<&str>::new());
+        assert_eq!(refined(&[("size", "medium")])["element_count"], 2);
+        assert_eq!(refined(&[("min_distance", "0.25")])["element_count"], 2);
+    }
+
+    #[test]
+    fn sorts_within_each_date() {
+        let feed = refined(&[("sort", "-velocity")]);
+
+        assert_eq!(ids(&feed["near_earth_objects"]["2024-01-01"]), ["d", "c"]);
+        assert_eq!(ids(&feed["near_earth_objects"]["2024-01-02"]), ["a", "b"]);
+    }
+
+    #[test]
+    fn flattens_across_dates() {
+        assert_eq!(ids(&refined(&[("flatten", "true")])["near_earth_objects"]), ["c", "d", "a", "b"]);
+        assert_eq!(ids(&refined(&[("flatten", "true"), ("sort", "miss_distance")])["near_earth_objects"]), ["b", "c", "a", "d"]);
+        assert_eq!(ids(&refined(&[("flatten", "true"), ("sort", "-diameter")])["near_earth_objects"]), ["a", "c", "d", "b"]);
+    }
+
+    #[test]
+    fn objects_without_a_measurement_are_kept_and_sort_last() {
+        let mut feed = feed();
+        feed["near_earth_objects"]["2024-01-01"][0]["close_approach_data"] = json!([]);
+        let endpoint = registry::find("/api/neo/feed").unwrap();
+        let query = vec![("flatten".to_string(), "1".to_string()), ("sort".to_string(), "miss_distance".to_string()), ("max_distance".to_string(), "0.35".to_string())];
+
+        let feed = refine(&ProxyRequest::new(endpoint, Default::default(), query), feed);
+
+        assert_eq!(ids(&feed["near_earth_objects"]), ["b", "a", "c"]);
+    }
+}
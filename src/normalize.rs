@@ -0,0 +1,299 @@
+//! Normalized responses, served for `normalize=true`.
+//!
+//! The cached payload is always the upstream one; normalizing happens when a
+//! response is rendered. The payload is deserialized into the endpoint's model in
+//! [`crate::models`] and serialized again, so every normalized response uses
+//! snake_case names, numbers instead of numeric strings and ISO-8601 timestamps
+//! in UTC. Fields the model doesn't declare are dropped. Each [`Normalizer`]
+//! also describes its output, for the OpenAPI spec.
+//!
+//! The SSD APIs' column tables (`fields` plus rows of strings) become one record
+//! per row, keyed by field name.
+
+use chrono::SecondsFormat;
+use schemars::{JsonSchema, Schema, SchemaGenerator};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::models::de::parse_timestamp;
+use crate::models::ssd::{CadResponse, CadSignature, FireballResponse, FireballSignature};
+use crate::models::{ApiResponse, PaginatedResponse};
+
+/// Re-emits an upstream payload through a model, and describes the result.
+#[derive(Debug, Clone, Copy)]
+pub struct Normalizer {
+    pub apply: fn(Value) -> serde_json::Result<Value>,
+    /// JSON Schema of what [`Normalizer::apply`] returns, for a generator
+    /// describing serialization.
+    pub schema: fn(&mut SchemaGenerator) -> Schema,
+    /// JSON Schema of the same payload in the [`ApiResponse`] served on `/api/v2`.
+    pub envelope: fn(&mut SchemaGenerator) -> Schema,
+}
+
+impl Normalizer {
+    /// A normalizer deserializing into `T` and serializing `T` as `O`.
+    const fn through<T, O>() -> Self
+    where
+        T: DeserializeOwned + Into<O>,
+        O: Serialize + JsonSchema,
+    {
+        Self { apply: apply::<T, O>, schema: schema::<O>, envelope: schema::<ApiResponse<O>> }
+    }
+}
+
+fn apply<T: DeserializeOwned + Into<O>, O: Serialize>(value: Value) -> serde_json::Result<Value> {
+    serde_json::to_value(serde_json::from_value::<T>(value)?.into())
+}
+
+fn schema<O: JsonSchema>(generator: &mut SchemaGenerator) -> Schema {
+    generator.subschema_for::<O>()
+}
+
+/// A single `T`.
+pub const fn one<T: DeserializeOwned + Serialize + JsonSchema>() -> Normalizer {
+    Normalizer::through::<T, T>()
+}
+
+/// A list of `T`.
+pub const fn list<T: DeserializeOwned + Serialize + JsonSchema>() -> Normalizer {
+    Normalizer::through::<Vec<T>, Vec<T>>()
+}
+
+/// One item or a list of them.
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+#[schemars(inline)]
+pub enum OneOrList<T> {
+    List(Vec<T>),
+    One(T),
+}
+
+/// A single `T` or a list of them, as APOD returns for date ranges and `count`.
+pub const fn one_or_list<T: DeserializeOwned + Serialize + JsonSchema>() -> Normalizer {
+    Normalizer::through::<OneOrList<T>, OneOrList<T>>()
+}
+
+/// A page of `I`s as a [`PaginatedResponse`], for `/api/v2`.
+pub const fn paginated<T, I>() -> Normalizer
+where
+    T: DeserializeOwned + Into<PaginatedResponse<I>>,
+    I: Serialize + JsonSchema,
+{
+    Normalizer::through::<T, PaginatedResponse<I>>()
+}
+
+/// Rows of an SSD table, each with one cell per column.
+type Rows = Vec<Vec<Option<String>>>;
+
+/// An SSD table payload: signature, row count, column names and rows.
+pub trait Table: DeserializeOwned {
+    type Signature: Serialize + JsonSchema;
+    /// Columns kept as text even when they look numeric, e.g. designations.
+    const TEXT: &'static [&'static str];
+    /// Columns holding timestamps.
+    const TIME: &'static [&'static str];
+
+    fn into_parts(self) -> (Self::Signature, u64, Vec<String>, Rows);
+}
+
+impl Table for CadResponse {
+    type Signature = CadSignature;
+    const TEXT: &'static [&'static str] = &["des", "orbit_id", "fullname", "body", "t_sigma_f"];
+    const TIME: &'static [&'static str] = &["cd"];
+
+    fn into_parts(self) -> (CadSignature, u64, Vec<String>, Rows) {
+        (self.signature, self.count, self.fields, self.data)
+    }
+}
+
+impl Table for FireballResponse {
+    type Signature = FireballSignature;
+    const TEXT: &'static [&'static str] = &["lat-dir", "lon-dir"];
+    const TIME: &'static [&'static str] = &["date"];
+
+    fn into_parts(self) -> (FireballSignature, u64, Vec<String>, Rows) {
+        (self.signature, self.count, self.fields, self.data)
+    }
+}
+
+/// An SSD table with one record per row.
+#[derive(Serialize, JsonSchema)]
+#[schemars(inline)]
+pub struct Records<S> {
+    pub signature: S,
+    pub count: u64,
+    /// One object per row, keyed by field name: numbers where they parse,
+    /// ISO-8601 timestamps, text, or `null` when empty.
+    pub data: Vec<Map<String, Value>>,
+}
+
+impl<T: Table> From<T> for Records<T::Signature> {
+    /// Field names use `_` for `-`; cells are numbers where they parse,
+    /// timestamps in ISO-8601 and `null` when empty.
+    fn from(table: T) -> Self {
+        let (signature, count, fields, rows) = table.into_parts();
+
+        let data = rows
+            .into_iter()
+            .map(|row| {
+                fields
+                    .iter()
+                    .zip(row)
+                    .map(|(field, cell)| (field.replace('-', "_"), cell_value(field, cell, T::TEXT, T::TIME)))
+                    .collect()
+            })
+            .collect();
+
+        Self { signature, count, data }
+    }
+}
+
+/// Turns a table into [`Records`].
+pub const fn table<T: Table>() -> Normalizer {
+    Normalizer::through::<T, Records<T::Signature>>()
+}
+
+fn cell_value(field: &str, cell: Option<String>, text: &[&str], time: &[&str]) -> Value {
+    let Some(cell) = cell.filter(|cell| !cell.trim().is_empty()) else {
+        return Value::Null;
+    };
+    if time.contains(&field) {
+        if let Some(timestamp) = parse_timestamp(&cell) {
+            return Value::String(timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true));
+        }
+    } else if !text.contains(&field) {
+        if let Some(number) = cell.trim().parse::<f64>().ok().filter(|n| n.is_finite()) {
+            return Value::from(number);
+        }
+    }
+    Value::String(cell)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::models::{apod, donki, neo};
+
+    #[test]
+    fn renames_fields_and_parses_timestamps() {
+        let upstream = json!([{
+            "flrID": "2024-01-01T00:10:00-FLR-001",
+            "instruments": [{"displayName": "GOES-P: EXIS 1.0-8.0"}],
+            "beginTime": "2024-01-01T00:10Z",
+            "peakTime": "2024-01-01T00:25Z",
+            "endTime": null,
+            "classType": "M1.1",
+            "sourceLocation": "N03E61",
+            "activeRegionNum": 13536,
+            "linkedEvents": null,
+            "link": "https://webtools.ccmc.gsfc.nasa.gov/DONKI/view/FLR/28000/-1"
+        }]);
+
+        let normalized = (list::<donki::FlrEvent>().apply)(upstream).unwrap();
+
+        assert_eq!(
+            normalized,
+            json!([{
+                "flr_id": "2024-01-01T00:10:00-FLR-001",
+                "instruments": [{"display_name": "GOES-P: EXIS 1.0-8.0"}],
+                "begin_time": "2024-01-01T00:10:00Z",
+                "peak_time": "2024-01-01T00:25:00Z",
+                "end_time": null,
+                "class_type": "M1.1",
+                "source_location": "N03E61",
+                "active_region_num": 13536,
+                "linked_events": null
+            }])
+        );
+    }
+
+    #[test]
+    fn converts_numeric_strings() {
+        let upstream = json!({
+            "close_approach_date": "2024-01-05",
+            "close_approach_date_full": "2024-Jan-05 12:34",
+            "epoch_date_close_approach": 1704458040000_i64,
+            "relative_velocity": {"kilometers_per_second": "12.5", "kilometers_per_hour": "45000", "miles_per_hour": "27961.7"},
+            "miss_distance": {"astronomical": "0.25", "lunar": "97.25", "kilometers": "37399467.5", "miles": "23239108.1"},
+            "orbiting_body": "Earth"
+        });
+
+        let normalized = (one::<neo::CloseApproach>().apply)(upstream).unwrap();
+
+        assert_eq!(normalized["close_approach_date_full"], "2024-01-05T12:34:00Z");
+        assert_eq!(normalized["relative_velocity"]["kilometers_per_second"], 12.5);
+        assert_eq!(normalized["miss_distance"]["lunar"], 97.25);
+    }
+
+    #[test]
+    fn accepts_one_or_a_list() {
+        let picture = json!({
+            "date": "2024-01-05",
+            "explanation": "...",
+            "media_type": "image",
+            "service_version": "v1",
+            "title": "Orion",
+            "url": "https://apod.nasa.gov/orion.jpg"
+        });
+
+        assert!((one_or_list::<apod::ApodResponse>().apply)(picture.clone()).unwrap().is_object());
+        assert_eq!((one_or_list::<apod::ApodResponse>().apply)(json!([picture])).unwrap().as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn pages_become_paginated_responses() {
+        let upstream = json!({
+            "links": {"self": "https://api.nasa.gov/neo/rest/v1/neo/browse?page=2&size=1"},
+            "page": {"size": 1, "total_elements": 40000, "total_pages": 40000, "number": 2},
+            "near_earth_objects": []
+        });
+
+        let normalized = (paginated::<neo::NeoBrowseResponse, neo::Neo>().apply)(upstream).unwrap();
+
+        assert_eq!(normalized, json!({"items": [], "page": 2, "total_pages": 40000, "total_items": 40000}));
+    }
+
+    #[test]
+    fn turns_tables_into_records() {
+        let upstream = json!({
+            "signature": {"source": "NASA/JPL SBDB Close Approach Data API", "version": "1.5"},
+            "count": "1",
+            "fields": ["des", "orbit_id", "jd", "cd", "dist", "v-rel", "h", "diameter"],
+            "data": [["2024 AA", "3", "2460315.023", "2024-Jan-05 12:34", "0.0123", "8.41", "24.1", null]]
+        });
+
+        let normalized = (table::<CadResponse>().apply)(upstream).unwrap();
+
+        assert_eq!(normalized["count"], 1);
+        assert_eq!(
+            normalized["data"],
+            json!([{
+                "des": "2024 AA",
+                "orbit_id": "3",
+                "jd": 2460315.023,
+                "cd": "2024-01-05T12:34:00Z",
+                "dist": 0.0123,
+                "v_rel": 8.41,
+                "h": 24.1,
+                "diameter": null
+            }])
+        );
+    }
+
+    #[test]
+    fn empty_tables_have_no_rows() {
+        let upstream = json!({"signature": {"source": "NASA/JPL Fireball Data API", "version": "1.0"}, "count": "0"});
+
+        let normalized = (table::<FireballResponse>().apply)(upstream).unwrap();
+
+        assert_eq!(normalized["count"], 0);
+        assert_eq!(normalized["data"], json!([]));
+    }
+
+    #[test]
+    fn rejects_payloads_that_do_not_match_the_model() {
+        assert!((list::<donki::FlrEvent>().apply)(json!({"error": "maintenance"})).is_err());
+    }
+}
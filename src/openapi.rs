@@ -0,0 +1,997 @@
+//! OpenAPI description of the proxy, generated from code.
+//!
+//! Paths and parameters come from [`crate::registry::ENDPOINTS`]: every declared
+//! path and query parameter is documented with a schema derived from its
+//! [`Kind`], allowed values and range, along with required parameters and
+//! defaults. Response bodies are the
+//! JSON Schemas of the serde types in [`crate::models`] as the upstream sends
+//! them; endpoints that take `normalize` also document the normalized form, as
+//! the endpoint's [`crate::normalize::Normalizer`] describes it, under
+//! `Normalized*` schema names.
+//! The prose (summaries, parameter descriptions) lives in [`OPERATIONS`], one
+//! entry per endpoint.
+//!
+//! Endpoints served on [`Version::V2`] get a second path under `/api/v2`, whose
+//! responses are the normalized schema wrapped in the
+//! [`crate::models::ApiResponse`] envelope.
+//!
+//! Routes outside the registry (usage reports, cache administration, the
+//! documentation itself) are not described path by path; they are listed with
+//! the reason in [`UNDOCUMENTED`], which the spec carries as
+//! `x-undocumented-routes`.
+//!
+//! `openapi.yaml` at the repository root is a checked-in copy of [`yaml`]; a test
+//! fails when it drifts. Regenerate it with
+//! `UPDATE_OPENAPI=1 cargo test openapi`.
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use schemars::generate::SchemaSettings;
+use schemars::transform::RecursiveTransform;
+use schemars::{JsonSchema, Schema, SchemaGenerator};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::error::ErrorResponse;
+use crate::middleware::auth::API_KEY_PARAM;
+use crate::middleware::client::API_KEY_HEADER;
+use crate::models::{apod, donki, earth, epic, exoplanets, mars, media, neo, ssd, tech};
+use crate::registry::{self, Endpoint, Format, Kind, Param, ProxyRequest, Version};
+
+const DESCRIPTION: &str = "\
+# NASA API Proxy Service 🚀
+
+A high-performance Cloudflare Worker providing unified access to various NASA APIs with intelligent caching, rate limiting, and enhanced features.
+
+## Features
+- **Unified Access**: Single endpoint for multiple NASA data sources
+- **Intelligent Caching**: Reduces API calls and improves response times
+- **Rate Limiting**: Fair usage protection across all endpoints
+- **CORS Support**: Browser-friendly API access
+- **No NASA API Key Required**: The proxy handles NASA API authentication
+
+## Getting Started
+All endpoints are available at `https://your-worker.workers.dev/api/*`
+
+Endpoints with a `normalize` parameter are also served at `/api/v2/*`: the normalized response wrapped in `{\"data\": ..., \"status\": \"success\", \"cached\": true}`, with paged lists as `{\"items\", \"page\", \"total_pages\", \"total_items\"}`.
+
+Client API keys are optional unless the deployment requires them. Send yours in the `X-API-Key` header or the `key` query parameter for a higher quota.
+
+## Rate Limits
+Every response carries `X-RateLimit-Limit`, `X-RateLimit-Remaining` and `X-RateLimit-Reset` headers. Requests over the limit get a 429 with `Retry-After`.
+";
+
+/// Tags grouping the operations, in display order.
+const TAGS: &[(&str, &str)] = &[
+    ("APOD", "Astronomy Picture of the Day - Daily space imagery with scientific explanations"),
+    ("NEO", "Near Earth Objects - Track asteroids and comets near Earth"),
+    ("DONKI", "Space Weather Database - Real-time space weather events and notifications"),
+    ("Earth", "Earth Imagery - Landsat satellite imagery of Earth locations"),
+    ("EPIC", "Earth Polychromatic Imaging Camera - Full disc Earth imagery"),
+    ("Mars", "Mars Rover Photos - Images from Curiosity, Opportunity, and Spirit rovers"),
+    ("Media", "NASA Image and Video Library - Search NASA's media archives"),
+    ("Exoplanets", "Exoplanet Archive - Query confirmed exoplanet data"),
+    ("SSD", "Solar System Dynamics - Orbital data and impact risk assessment"),
+    ("Tech Transfer", "Technology Transfer - NASA patents, software, and spinoffs"),
+];
+
+/// Routes the worker serves that `paths` leaves out, with the reason. A trailing
+/// `*` matches every route with that prefix.
+const UNDOCUMENTED: &[(&str, &str)] = &[
+    ("/", "Landing page"),
+    ("/health", "Health check"),
+    ("/api/docs*", "This documentation and Swagger UI"),
+    ("/api/usage", "The calling client's usage report; see the README"),
+    ("/api/admin/*", "Usage reports and cache administration for the admin token holder; see the README"),
+];
+
+/// Where the generators put the schemas of named types.
+const SCHEMAS: &str = "#/components/schemas/";
+
+/// Prefix of the names of normalized schemas, keeping them apart from the
+/// upstream schemas of the same models.
+const NORMALIZED: &str = "Normalized";
+
+/// Successful response body of an operation.
+enum Body {
+    /// JSON described by the schema the function registers.
+    Json(fn(&mut SchemaGenerator) -> Value),
+    /// Binary payload of the given content type.
+    Binary(&'static str),
+}
+
+/// Prose for one endpoint of the registry.
+struct Operation {
+    /// Route of the [`Endpoint`] this documents.
+    route: &'static str,
+    tag: &'static str,
+    summary: &'static str,
+    description: &'static str,
+    response: Body,
+    /// Descriptions of the endpoint's parameters, by name.
+    params: &'static [(&'static str, &'static str)],
+}
+
+fn one<T: JsonSchema>(gen: &mut SchemaGenerator) -> Value {
+    json!(gen.subschema_for::<T>())
+}
+
+fn list<T: JsonSchema>(gen: &mut SchemaGenerator) -> Value {
+    json!(gen.subschema_for::<Vec<T>>())
+}
+
+fn one_or_list<T: JsonSchema>(gen: &mut SchemaGenerator) -> Value {
+    json!({ "oneOf": [one::<T>(gen), list::<T>(gen)] })
+}
+
+const NORMALIZE: &str = "Re-emit the response through its model: snake_case names, \
+numbers instead of numeric strings and ISO-8601 timestamps. SSD tables become one record per row.";
+
+const FIELDS: &str = "Comma-separated paths to return, dotted (`photos[].camera.name`) or JSON pointers \
+(`/photos/camera/name`). Paths through an array apply to every element and `*` matches every member of an object.";
+
+const DONKI: &[(&str, &str)] = &[
+    ("startDate", "Start of the date range (defaults to 30 days before endDate)"),
+    ("endDate", "End of the date range (defaults to today)"),
+];
+
+const ROVER: (&str, &str) = ("rover", "Rover name");
+
+const NASA_ID: &[(&str, &str)] = &[("nasa_id", "NASA asset ID")];
+
+const TECH_TRANSFER: &[(&str, &str)] = &[
+    ("query", "Search term"),
+    ("center", "NASA center"),
+    ("year", "Year"),
+    ("word", "Search term (alias of query)"),
+];
+
+const COORDINATES: &[(&str, &str)] = &[
+    ("lat", "Latitude"),
+    ("lon", "Longitude"),
+    ("date", "Date of imagery; the closest available date is used"),
+    ("dim", "Width and height of the image in degrees"),
+];
+
+static OPERATIONS: &[Operation] = &[
+    Operation {
+        route: "/api/apod",
+        tag: "APOD",
+        summary: "Get Astronomy Picture of the Day",
+        description: "Retrieve the Astronomy Picture of the Day with explanation. Returns a list when a date range or count is given.",
+        response: Body::Json(one_or_list::<apod::ApodResponse>),
+        params: &[
+            ("date", "Date of the APOD image to retrieve"),
+            ("start_date", "Start of date range (requires end_date)"),
+            ("end_date", "End of date range (requires start_date)"),
+            ("count", "Return random APODs (cannot be used with date parameters)"),
+            ("thumbs", "Include thumbnail URL for video APODs"),
+        ],
+    },
+    Operation {
+        route: "/api/neo/feed",
+        tag: "NEO",
+        summary: "Get NEO feed by date range",
+        description: "Retrieve a list of Near Earth Objects within a date range, including close approach data. \
+The worker can filter, sort and flatten the feed; element_count counts the objects returned.",
+        response: Body::Json(one::<neo::NeoFeedResponse>),
+        params: &[
+            ("start_date", "Start date for NEO search"),
+            ("end_date", "End date for NEO search (at most NEO_FEED_MAX_DAYS, 31 by default, after start_date; ranges over 7 days are fetched in windows and merged)"),
+            ("hazardous", "Only potentially hazardous asteroids"),
+            ("size", "Size class by maximum estimated diameter: small (< 100 m), medium (100 m to 1 km) or large (>= 1 km)"),
+            ("min_distance", "Minimum miss distance of the first close approach, in AU"),
+            ("max_distance", "Maximum miss distance of the first close approach, in AU"),
+            ("sort", "Sort by miss distance, velocity, diameter or approach time; prefix with - for descending"),
+            ("flatten", "Return near_earth_objects as one list across all dates instead of a map by date"),
+        ],
+    },
+    Operation {
+        route: "/api/neo/browse",
+        tag: "NEO",
+        summary: "Browse all NEOs with pagination",
+        description: "Browse the overall Near Earth Object dataset with pagination support",
+        response: Body::Json(one::<neo::NeoBrowseResponse>),
+        params: &[("page", "Page number (0-indexed)"), ("size", "Number of NEOs per page")],
+    },
+    Operation {
+        route: "/api/neo/:asteroid_id",
+        tag: "NEO",
+        summary: "Get specific NEO by ID",
+        description: "Retrieve detailed information about a specific Near Earth Object",
+        response: Body::Json(one::<neo::Neo>),
+        params: &[("asteroid_id", "Asteroid SPK-ID")],
+    },
+    Operation {
+        route: "/api/donki/cme",
+        tag: "DONKI",
+        summary: "Get Coronal Mass Ejections",
+        description: "Retrieve Coronal Mass Ejection (CME) events from the Space Weather Database",
+        response: Body::Json(list::<donki::CmeEvent>),
+        params: DONKI,
+    },
+    Operation {
+        route: "/api/donki/cme-analysis",
+        tag: "DONKI",
+        summary: "Get CME Analysis data",
+        description: "Retrieve detailed Coronal Mass Ejection analysis data",
+        response: Body::Json(list::<donki::CmeAnalysis>),
+        params: &[
+            DONKI[0],
+            DONKI[1],
+            ("mostAccurateOnly", "Only return the most accurate analysis of each CME"),
+            ("completeEntryOnly", "Only return complete entries"),
+            ("speed", "Lower limit for CME speed (km/s)"),
+            ("halfAngle", "Lower limit for CME half-angle (degrees)"),
+            ("catalog", "Catalog: ALL, SWRC_CATALOG or JANG_ET_AL_CATALOG"),
+            ("keyword", "Keyword to filter analyses by"),
+        ],
+    },
+    Operation {
+        route: "/api/donki/gst",
+        tag: "DONKI",
+        summary: "Get Geomagnetic Storms",
+        description: "Retrieve Geomagnetic Storm (GST) events",
+        response: Body::Json(list::<donki::GstEvent>),
+        params: DONKI,
+    },
+    Operation {
+        route: "/api/donki/ips",
+        tag: "DONKI",
+        summary: "Get Interplanetary Shocks",
+        description: "Retrieve Interplanetary Shock (IPS) events",
+        response: Body::Json(list::<donki::IpsEvent>),
+        params: &[
+            DONKI[0],
+            DONKI[1],
+            ("location", "Location: Earth, MESSENGER, STEREO A or STEREO B"),
+            ("catalog", "Catalog: SWRC_CATALOG or WINSLOW_MESSENGER_ICME_CATALOG"),
+        ],
+    },
+    Operation {
+        route: "/api/donki/flr",
+        tag: "DONKI",
+        summary: "Get Solar Flares",
+        description: "Retrieve Solar Flare (FLR) events",
+        response: Body::Json(list::<donki::FlrEvent>),
+        params: DONKI,
+    },
+    Operation {
+        route: "/api/donki/sep",
+        tag: "DONKI",
+        summary: "Get Solar Energetic Particles",
+        description: "Retrieve Solar Energetic Particle (SEP) events",
+        response: Body::Json(list::<donki::SepEvent>),
+        params: DONKI,
+    },
+    Operation {
+        route: "/api/donki/mpc",
+        tag: "DONKI",
+        summary: "Get Magnetopause Crossings",
+        description: "Retrieve Magnetopause Crossing (MPC) events",
+        response: Body::Json(list::<donki::MpcEvent>),
+        params: DONKI,
+    },
+    Operation {
+        route: "/api/donki/rbe",
+        tag: "DONKI",
+        summary: "Get Radiation Belt Enhancements",
+        description: "Retrieve Radiation Belt Enhancement (RBE) events",
+        response: Body::Json(list::<donki::RbeEvent>),
+        params: DONKI,
+    },
+    Operation {
+        route: "/api/donki/hss",
+        tag: "DONKI",
+        summary: "Get High Speed Streams",
+        description: "Retrieve High Speed Stream (HSS) events",
+        response: Body::Json(list::<donki::HssEvent>),
+        params: DONKI,
+    },
+    Operation {
+        route: "/api/donki/wsa-enlil",
+        tag: "DONKI",
+        summary: "Get WSA+Enlil Simulations",
+        description: "Retrieve WSA+Enlil solar wind prediction simulations",
+        response: Body::Json(list::<donki::WsaEnlilSimulation>),
+        params: DONKI,
+    },
+    Operation {
+        route: "/api/donki/notifications",
+        tag: "DONKI",
+        summary: "Get Space Weather Notifications",
+        description: "Retrieve space weather notifications and alerts",
+        response: Body::Json(list::<donki::Notification>),
+        params: &[DONKI[0], DONKI[1], ("type", "Notification type; all by default")],
+    },
+    Operation {
+        route: "/api/earth/imagery",
+        tag: "Earth",
+        summary: "Get Earth imagery for location",
+        description: "Retrieve Landsat 8 satellite imagery for a specific Earth location",
+        response: Body::Binary("image/png"),
+        params: COORDINATES,
+    },
+    Operation {
+        route: "/api/earth/assets",
+        tag: "Earth",
+        summary: "Get available imagery dates",
+        description: "Retrieve dates for available Landsat 8 imagery at a location",
+        response: Body::Json(list::<earth::EarthAsset>),
+        params: COORDINATES,
+    },
+    Operation {
+        route: "/api/epic/natural/all",
+        tag: "EPIC",
+        summary: "Get all natural color image dates",
+        description: "List the dates that have natural color images of Earth",
+        response: Body::Json(list::<epic::EpicDate>),
+        params: &[],
+    },
+    Operation {
+        route: "/api/epic/natural/date/:date",
+        tag: "EPIC",
+        summary: "Get natural color images by date",
+        description: "Retrieve natural color Earth images for a specific date",
+        response: Body::Json(list::<epic::EpicImage>),
+        params: &[("date", "Date to retrieve images")],
+    },
+    Operation {
+        route: "/api/epic/enhanced/all",
+        tag: "EPIC",
+        summary: "Get all enhanced color image dates",
+        description: "List the dates that have enhanced color images of Earth",
+        response: Body::Json(list::<epic::EpicDate>),
+        params: &[],
+    },
+    Operation {
+        route: "/api/epic/enhanced/date/:date",
+        tag: "EPIC",
+        summary: "Get enhanced color images by date",
+        description: "Retrieve enhanced color Earth images for a specific date",
+        response: Body::Json(list::<epic::EpicImage>),
+        params: &[("date", "Date to retrieve images")],
+    },
+    Operation {
+        route: "/api/mars-photos/:rover/photos",
+        tag: "Mars",
+        summary: "Get Mars rover photos",
+        description: "Retrieve photos taken by a specific Mars rover on a sol or Earth date",
+        response: Body::Json(one::<mars::MarsPhotosResponse>),
+        params: &[
+            ROVER,
+            ("sol", "Martian sol (day) of photos"),
+            ("earth_date", "Earth date of photos (use either sol or earth_date)"),
+            ("camera", "Camera abbreviation; curiosity has fhaz, rhaz, mast, chemcam, mahli, mardi and navcam, opportunity and spirit have fhaz, rhaz, navcam, pancam and minites"),
+            ("page", "Page number for pagination, 25 photos per page"),
+        ],
+    },
+    Operation {
+        route: "/api/mars-photos/:rover/latest",
+        tag: "Mars",
+        summary: "Get latest Mars rover photos",
+        description: "Retrieve the most recent photos from a Mars rover",
+        response: Body::Json(one::<mars::MarsPhotosResponse>),
+        params: &[ROVER],
+    },
+    Operation {
+        route: "/api/mars-photos/manifests/:rover",
+        tag: "Mars",
+        summary: "Get Mars rover manifest",
+        description: "Retrieve mission manifest for a Mars rover",
+        response: Body::Json(one::<mars::MarsManifest>),
+        params: &[ROVER],
+    },
+    Operation {
+        route: "/api/techtransfer/patents",
+        tag: "Tech Transfer",
+        summary: "Search NASA patents",
+        description: "Search NASA's patent portfolio",
+        response: Body::Json(one::<tech::TechTransferResponse>),
+        params: TECH_TRANSFER,
+    },
+    Operation {
+        route: "/api/techtransfer/patents-issued",
+        tag: "Tech Transfer",
+        summary: "Search issued NASA patents",
+        description: "Search NASA's issued patents only",
+        response: Body::Json(one::<tech::TechTransferResponse>),
+        params: TECH_TRANSFER,
+    },
+    Operation {
+        route: "/api/techtransfer/software",
+        tag: "Tech Transfer",
+        summary: "Search NASA software",
+        description: "Search NASA's software catalog",
+        response: Body::Json(one::<tech::TechTransferResponse>),
+        params: TECH_TRANSFER,
+    },
+    Operation {
+        route: "/api/techtransfer/spinoffs",
+        tag: "Tech Transfer",
+        summary: "Search NASA spinoffs",
+        description: "Search NASA technology spinoffs",
+        response: Body::Json(one::<tech::TechTransferResponse>),
+        params: TECH_TRANSFER,
+    },
+    Operation {
+        route: "/api/media/search",
+        tag: "Media",
+        summary: "Search NASA media library",
+        description: "Search NASA's image and video library",
+        response: Body::Json(one::<media::MediaSearchResponse>),
+        params: &[
+            ("q", "Free text search terms"),
+            ("center", "NASA center"),
+            ("description", "Search in descriptions"),
+            ("keywords", "Comma-separated keywords"),
+            ("location", "Geographic location"),
+            ("media_type", "Comma-separated media types: image, video, audio"),
+            ("nasa_id", "NASA asset ID"),
+            ("page", "Page number"),
+            ("page_size", "Results per page"),
+            ("photographer", "Photographer name"),
+            ("title", "Search in titles"),
+            ("year_start", "Start year"),
+            ("year_end", "End year"),
+        ],
+    },
+    Operation {
+        route: "/api/media/asset/:nasa_id",
+        tag: "Media",
+        summary: "Get media asset files",
+        description: "Retrieve file URLs for a specific NASA media asset",
+        response: Body::Json(one::<media::MediaAssetResponse>),
+        params: NASA_ID,
+    },
+    Operation {
+        route: "/api/media/metadata/:nasa_id",
+        tag: "Media",
+        summary: "Get media metadata",
+        description: "Retrieve the location of the metadata file for a NASA media asset",
+        response: Body::Json(one::<media::MediaLocation>),
+        params: NASA_ID,
+    },
+    Operation {
+        route: "/api/media/captions/:nasa_id",
+        tag: "Media",
+        summary: "Get media captions",
+        description: "Retrieve the location of the captions file for a NASA video asset",
+        response: Body::Json(one::<media::MediaLocation>),
+        params: NASA_ID,
+    },
+    Operation {
+        route: "/api/exoplanets/query",
+        tag: "Exoplanets",
+        summary: "Query exoplanet archive",
+        description: "Query the NASA Exoplanet Archive using TAP (Table Access Protocol). Formats other than json are returned as text in the data field.",
+        response: Body::Json(one::<exoplanets::ExoplanetResponse>),
+        params: &[("query", "ADQL query, e.g. select pl_name from ps"), ("format", "Output format")],
+    },
+    Operation {
+        route: "/api/ssd/cad",
+        tag: "SSD",
+        summary: "Get close approach data",
+        description: "Query close approach data for asteroids and comets",
+        response: Body::Json(one::<ssd::CadResponse>),
+        params: &[
+            ("date-min", "Start date for close approaches"),
+            ("date-max", "End date for close approaches"),
+            ("dist-min", "Minimum approach distance (au, or with a unit suffix such as LD)"),
+            ("dist-max", "Maximum approach distance (au, or with a unit suffix such as LD)"),
+            ("h-min", "Minimum absolute magnitude"),
+            ("h-max", "Maximum absolute magnitude"),
+            ("v-inf-min", "Minimum velocity at infinity (km/s)"),
+            ("v-inf-max", "Maximum velocity at infinity (km/s)"),
+            ("pha", "Limit to potentially hazardous asteroids"),
+            ("nea", "Limit to near-Earth asteroids"),
+            ("comet", "Limit to comets"),
+            ("nea-comet", "Limit to near-Earth asteroids and comets"),
+            ("neo", "Limit to near-Earth objects"),
+            ("des", "Object designation"),
+            ("spk", "Object SPK-ID"),
+            ("body", "Close-approach body, e.g. earth; ALL for every body"),
+            ("sort", "Sort field, prefixed with - for descending order"),
+            ("limit", "Maximum number of results"),
+            ("fullname", "Include the full object name"),
+            ("diameter", "Include the object diameter"),
+        ],
+    },
+    Operation {
+        route: "/api/ssd/sbdb",
+        tag: "SSD",
+        summary: "Small Body Database lookup",
+        description: "Look up a specific asteroid or comet",
+        response: Body::Json(one::<ssd::SbdbResponse>),
+        params: &[
+            ("sstr", "Search string (name, designation, or SPK-ID)"),
+            ("des", "Object designation"),
+            ("spk", "Object SPK-ID"),
+            ("cad", "Include close-approach data"),
+            ("ca-data", "Include close-approach data (alias of cad)"),
+            ("phys-par", "Include physical parameters"),
+            ("discovery", "Include discovery data"),
+            ("full-prec", "Return numbers in full precision"),
+        ],
+    },
+    Operation {
+        route: "/api/ssd/sentry",
+        tag: "SSD",
+        summary: "Get Sentry impact risk data",
+        description: "Query potential future Earth impact risks",
+        response: Body::Json(one::<ssd::SentryResponse>),
+        params: &[
+            ("des", "Object designation"),
+            ("spk", "Object SPK-ID"),
+            ("all", "Return all virtual impactors"),
+            ("removed", "List objects removed from the risk table"),
+            ("ip-min", "Minimum impact probability"),
+            ("ps-min", "Minimum Palermo scale"),
+            ("h-max", "Maximum absolute magnitude"),
+            ("days", "Only objects updated within this many days"),
+        ],
+    },
+    Operation {
+        route: "/api/ssd/scout",
+        tag: "SSD",
+        summary: "Get Scout trajectory analysis",
+        description: "Get trajectory analysis for recently discovered objects",
+        response: Body::Json(one::<ssd::ScoutResponse>),
+        params: &[("tdes", "Temporary designation")],
+    },
+    Operation {
+        route: "/api/ssd/nhats",
+        tag: "SSD",
+        summary: "Get NHATS objects",
+        description: "Query Near-Earth objects suitable for human spaceflight missions",
+        response: Body::Json(one::<ssd::NhatsResponse>),
+        params: &[
+            ("dv", "Maximum delta-v (km/s)"),
+            ("dur", "Maximum mission duration (days)"),
+            ("stay", "Minimum stay time (days)"),
+            ("launch", "Launch window, e.g. 2025-2030"),
+            ("h", "Maximum absolute magnitude (alias of h-max)"),
+            ("h-max", "Maximum absolute magnitude"),
+            ("occ", "Maximum orbit condition code"),
+            ("des", "Object designation"),
+            ("spk", "Object SPK-ID"),
+        ],
+    },
+    Operation {
+        route: "/api/ssd/fireballs",
+        tag: "SSD",
+        summary: "Get fireball events",
+        description: "Query atmospheric fireball events detected by US Government sensors",
+        response: Body::Json(one::<ssd::FireballResponse>),
+        params: &[
+            ("date-min", "Start date"),
+            ("date-max", "End date"),
+            ("energy-min", "Minimum total radiated energy (10^10 joules)"),
+            ("energy-max", "Maximum total radiated energy (10^10 joules)"),
+            ("impact-e-min", "Minimum impact energy (kilotons)"),
+            ("impact-e-max", "Maximum impact energy (kilotons)"),
+            ("vel-min", "Minimum velocity (km/s)"),
+            ("vel-max", "Maximum velocity (km/s)"),
+            ("alt-min", "Minimum altitude (km)"),
+            ("alt-max", "Maximum altitude (km)"),
+            ("req-loc", "Only events with a location"),
+            ("req-alt", "Only events with an altitude"),
+            ("req-vel", "Only events with a velocity"),
+            ("req-vel-comp", "Only events with velocity components"),
+            ("vel-comp", "Include velocity components"),
+            ("sort", "Sort field, prefixed with - for descending order"),
+            ("limit", "Maximum number of results"),
+        ],
+    },
+];
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Spec {
+    openapi: &'static str,
+    info: Value,
+    servers: Value,
+    tags: Vec<Value>,
+    security: Value,
+    paths: BTreeMap<String, PathItem>,
+    components: Components,
+    #[serde(rename = "x-undocumented-routes")]
+    undocumented_routes: Vec<Value>,
+}
+
+#[derive(Serialize)]
+struct PathItem {
+    get: OperationObject,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OperationObject {
+    tags: [&'static str; 1],
+    summary: &'static str,
+    description: &'static str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    parameters: Vec<Parameter>,
+    responses: BTreeMap<&'static str, Value>,
+}
+
+#[derive(Serialize)]
+struct Parameter {
+    name: &'static str,
+    #[serde(rename = "in")]
+    location: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'static str>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    required: bool,
+    schema: Value,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Components {
+    schemas: BTreeMap<String, Value>,
+    responses: BTreeMap<&'static str, Value>,
+    security_schemes: Value,
+}
+
+/// JSON Schema of a parameter value, with its constraints and its default when
+/// the registry has one.
+fn param_schema(param: &Param, default: Option<&str>) -> Value {
+    let (mut schema, default) = match param.kind {
+        Kind::Text | Kind::Lowercase => (json!({ "type": "string" }), default.map(|d| json!(d))),
+        Kind::Date => (json!({ "type": "string", "format": "date" }), default.map(|d| json!(d))),
+        Kind::Integer => (json!({ "type": "integer" }), default.and_then(|d| d.parse::<i64>().ok()).map(|d| json!(d))),
+        Kind::Number => (json!({ "type": "number" }), default.and_then(|d| d.parse::<f64>().ok()).map(|d| json!(d))),
+        Kind::Flag => (json!({ "type": "boolean" }), default.and_then(|d| d.parse::<bool>().ok()).map(|d| json!(d))),
+    };
+    if !param.values.is_empty() {
+        schema["enum"] = json!(param.values);
+    }
+    if let Some(min) = param.min {
+        schema["minimum"] = json!(min);
+    }
+    if let Some(max) = param.max {
+        schema["maximum"] = json!(max);
+    }
+    if let Some(default) = default {
+        schema["default"] = default;
+    }
+    schema
+}
+
+/// Parameters of `endpoint` on `version`; v2 responses are always normalized,
+/// so `normalize` is only listed on v1.
+fn parameters(endpoint: &Endpoint, operation: &Operation, version: Version) -> Vec<Parameter> {
+    let description = |name: &str| operation.params.iter().find(|(n, _)| *n == name).map(|(_, d)| *d);
+
+    let path = endpoint.route_params().map(|name| {
+        let schema = match endpoint.path.iter().find(|p| p.name == name) {
+            Some(param) => param_schema(param, None),
+            None => json!({ "type": "string" }),
+        };
+        Parameter { name, location: "path", description: description(name), required: true, schema }
+    });
+    let query = endpoint.query_params().iter().chain(endpoint.local).map(|param| {
+        let default = endpoint.defaults.iter().find(|(n, _)| *n == param.name).map(|(_, v)| *v);
+        Parameter {
+            name: param.name,
+            location: "query",
+            description: description(param.name),
+            required: endpoint.required.contains(&param.name),
+            schema: param_schema(param, default),
+        }
+    });
+    let option = |name: &str, description: &'static str, default: Option<&str>| {
+        let param = registry::PROXY_PARAMS.iter().find(|p| p.name == name).unwrap();
+        Parameter {
+            name: param.name,
+            location: "query",
+            description: Some(description),
+            required: false,
+            schema: param_schema(param, default),
+        }
+    };
+    let normalize = endpoint
+        .normalize
+        .filter(|_| version == Version::V1)
+        .map(|_| option(registry::NORMALIZE_PARAM, NORMALIZE, Some("false")));
+    let fields = (endpoint.format != Format::Binary).then(|| option(registry::FIELDS_PARAM, FIELDS, None));
+    path.chain(query).chain(normalize).chain(fields).collect()
+}
+
+/// Generator for the normalized side of the models: what the worker serializes
+/// rather than what the upstream sends. Its schemas refer to each other by
+/// [`NORMALIZED`] names.
+fn normalized_generator() -> SchemaGenerator {
+    SchemaSettings::openapi3()
+        .for_serialize()
+        .with_transform(RecursiveTransform(|schema: &mut Schema| {
+            if let Some(Value::String(reference)) = schema.get_mut("$ref") {
+                if let Some(name) = reference.strip_prefix(SCHEMAS) {
+                    *reference = format!("{SCHEMAS}{NORMALIZED}{name}");
+                }
+            }
+        }))
+        .into_generator()
+}
+
+/// A schema a [`crate::normalize::Normalizer`] describes, from [`normalized_generator`].
+fn normalized_schema(schema: fn(&mut SchemaGenerator) -> Schema, normalized: &mut SchemaGenerator) -> Value {
+    let mut schema = schema(normalized);
+    for transform in normalized.transforms_mut() {
+        transform.transform(&mut schema);
+    }
+    json!(schema)
+}
+
+fn responses(
+    endpoint: &Endpoint,
+    operation: &Operation,
+    version: Version,
+    gen: &mut SchemaGenerator,
+    normalized: &mut SchemaGenerator,
+) -> BTreeMap<&'static str, Value> {
+    let (description, content) = match (version, &operation.response, endpoint.normalize) {
+        (Version::V2, _, _) => {
+            // Every v2 request is normalized the same way
+            let normalizer = endpoint.normalizer(version, &ProxyRequest::default()).expect("v2 endpoints have a normalizer");
+            (
+                "Successful response: the normalized payload in the v2 envelope",
+                json!({ "application/json": { "schema": normalized_schema(normalizer.envelope, normalized) } }),
+            )
+        }
+        (Version::V1, &Body::Binary(content_type), _) => {
+            ("Successful response", json!({ content_type: { "schema": { "type": "string", "format": "binary" } } }))
+        }
+        (Version::V1, &Body::Json(schema), None) => ("Successful response", json!({ "application/json": { "schema": schema(gen) } })),
+        (Version::V1, &Body::Json(schema), Some(normalizer)) => (
+            "Successful response: the upstream payload, or its normalized form with `normalize=true`",
+            json!({ "application/json": { "schema": { "anyOf": [schema(gen), normalized_schema(normalizer.schema, normalized)] } } }),
+        ),
+    };
+    let mut responses = BTreeMap::from([
+        ("200", json!({ "description": description, "content": content })),
+        ("400", json!({ "$ref": "#/components/responses/BadRequest" })),
+        ("429", json!({ "$ref": "#/components/responses/RateLimited" })),
+        ("500", json!({ "$ref": "#/components/responses/InternalError" })),
+        ("502", json!({ "$ref": "#/components/responses/UpstreamError" })),
+    ]);
+    if endpoint.route_params().next().is_some() {
+        responses.insert("404", json!({ "$ref": "#/components/responses/NotFound" }));
+    }
+    responses
+}
+
+fn error_response(description: &str, error: Value) -> Value {
+    json!({
+        "description": description,
+        "content": { "application/json": { "schema": error } },
+    })
+}
+
+fn spec() -> Spec {
+    let mut gen = SchemaSettings::openapi3().into_generator();
+    let mut normalized = normalized_generator();
+
+    let mut paths = BTreeMap::new();
+    for endpoint in registry::ENDPOINTS {
+        let Some(operation) = OPERATIONS.iter().find(|op| op.route == endpoint.route) else {
+            continue;
+        };
+        for &version in endpoint.versions() {
+            let path = endpoint
+                .route_on(version)
+                .split('/')
+                .map(|segment| match segment.strip_prefix(':') {
+                    Some(name) => format!("{{{name}}}"),
+                    None => segment.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("/");
+            let get = OperationObject {
+                tags: [operation.tag],
+                summary: operation.summary,
+                description: operation.description,
+                parameters: parameters(endpoint, operation, version),
+                responses: responses(endpoint, operation, version, &mut gen, &mut normalized),
+            };
+            paths.insert(path, PathItem { get });
+        }
+    }
+
+    let error = one::<ErrorResponse>(&mut gen);
+    let mut rate_limited = error_response("Rate limit exceeded", error.clone());
+    rate_limited["headers"] = json!({
+        "X-RateLimit-Limit": { "description": "Request limit per window", "schema": { "type": "integer" } },
+        "X-RateLimit-Remaining": { "description": "Remaining requests in window", "schema": { "type": "integer" } },
+        "X-RateLimit-Reset": { "description": "Time when the window resets (Unix timestamp)", "schema": { "type": "integer" } },
+        "Retry-After": { "description": "Seconds to wait before retrying", "schema": { "type": "integer" } },
+    });
+    let responses = BTreeMap::from([
+        ("BadRequest", error_response("Invalid or missing parameters, each listed in problems", error.clone())),
+        ("NotFound", error_response("Resource not found", error.clone())),
+        ("RateLimited", rate_limited),
+        ("InternalError", error_response("Internal server error", error.clone())),
+        ("UpstreamError", error_response("The NASA API failed, timed out or is out of quota (502, 503 or 504)", error)),
+    ]);
+
+    Spec {
+        openapi: "3.0.3",
+        info: json!({
+            "title": "NASA API Proxy Service",
+            "description": DESCRIPTION,
+            "version": env!("CARGO_PKG_VERSION"),
+            "contact": { "name": "API Support", "url": "https://github.com/guitaripod/nasa-rs" },
+            "license": { "name": "MIT", "url": "https://opensource.org/licenses/MIT" },
+        }),
+        servers: json!([
+            { "url": "https://your-worker.workers.dev", "description": "Your self-hosted Cloudflare Worker (replace with your own deployment URL)" },
+            { "url": "http://localhost:8787", "description": "Local development (wrangler dev)" },
+        ]),
+        tags: TAGS.iter().map(|(name, description)| json!({ "name": name, "description": description })).collect(),
+        // Client keys are optional, so anonymous requests are listed too
+        security: json!([{}, { "ApiKeyHeader": [] }, { "ApiKeyQuery": [] }]),
+        paths,
+        components: Components {
+            schemas: gen
+                .take_definitions(true)
+                .into_iter()
+                .chain(normalized.take_definitions(true).into_iter().map(|(name, schema)| (format!("{NORMALIZED}{name}"), schema)))
+                .collect(),
+            responses,
+            security_schemes: json!({
+                "ApiKeyHeader": { "type": "apiKey", "in": "header", "name": API_KEY_HEADER },
+                "ApiKeyQuery": { "type": "apiKey", "in": "query", "name": API_KEY_PARAM },
+            }),
+        },
+        undocumented_routes: UNDOCUMENTED.iter().map(|(route, reason)| json!({ "route": route, "reason": reason })).collect(),
+    }
+}
+
+/// The spec as JSON.
+pub fn json() -> &'static str {
+    static JSON: OnceLock<String> = OnceLock::new();
+    JSON.get_or_init(|| serde_json::to_string_pretty(&spec()).expect("spec serializes to JSON"))
+}
+
+/// The spec as YAML, as checked in at `openapi.yaml`.
+pub fn yaml() -> &'static str {
+    static YAML: OnceLock<String> = OnceLock::new();
+    YAML.get_or_init(|| serde_yaml::to_string(&spec()).expect("spec serializes to YAML"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::Format;
+    use crate::router;
+    use worker::Method;
+
+    #[test]
+    fn documents_every_endpoint() {
+        for endpoint in registry::ENDPOINTS {
+            let operation = OPERATIONS.iter().find(|op| op.route == endpoint.route);
+            let Some(operation) = operation else {
+                panic!("{} has no entry in OPERATIONS", endpoint.route);
+            };
+
+            let binary = matches!(operation.response, Body::Binary(_));
+            assert_eq!(binary, endpoint.format == Format::Binary, "{}", endpoint.route);
+            let declared = parameters(endpoint, operation, Version::V1);
+            for (name, _) in operation.params {
+                assert!(declared.iter().any(|p| p.name == *name), "{} documents undeclared parameter {name}", endpoint.route);
+            }
+        }
+        assert_eq!(OPERATIONS.len(), registry::ENDPOINTS.len());
+    }
+
+    /// Whether `route` is covered by an [`UNDOCUMENTED`] pattern.
+    fn undocumented(route: &str) -> bool {
+        UNDOCUMENTED.iter().any(|(pattern, _)| match pattern.strip_suffix('*') {
+            Some(prefix) => route.starts_with(prefix),
+            None => route == *pattern,
+        })
+    }
+
+    #[test]
+    fn every_route_is_documented_or_listed_as_undocumented() {
+        let spec: Value = serde_json::from_str(json()).unwrap();
+        let routes = router::routes();
+
+        for route in routes {
+            let path = route.pattern.split('/').map(|s| s.strip_prefix(':').map_or(s.to_string(), |name| format!("{{{name}}}")));
+            let path = path.collect::<Vec<_>>().join("/");
+            let documented = route.method == Method::Get && spec["paths"].get(&path).is_some();
+            assert!(documented || undocumented(&route.pattern), "{:?} {} is neither in paths nor in UNDOCUMENTED", route.method, route.pattern);
+        }
+        for (pattern, _) in UNDOCUMENTED {
+            let prefix = pattern.trim_end_matches('*');
+            assert!(routes.iter().any(|route| route.pattern.starts_with(prefix)), "UNDOCUMENTED lists {pattern}, which no route matches");
+        }
+        assert_eq!(spec["x-undocumented-routes"].as_array().unwrap().len(), UNDOCUMENTED.len());
+    }
+
+    #[test]
+    fn describes_parameters_from_the_registry() {
+        let spec: Value = serde_json::from_str(json()).unwrap();
+        let params = &spec["paths"]["/api/neo/browse"]["get"]["parameters"];
+
+        assert_eq!(params[0]["name"], "page");
+        assert_eq!(params[0]["schema"], json!({ "type": "integer", "minimum": 0, "default": 0 }));
+        let rover = &spec["paths"]["/api/mars-photos/{rover}/photos"]["get"]["parameters"][0];
+        assert_eq!(rover["in"], "path");
+        assert_eq!(rover["required"], true);
+        let sstr = &spec["paths"]["/api/ssd/sbdb"]["get"]["parameters"][0];
+        assert_eq!(sstr["required"], true);
+    }
+
+    #[test]
+    fn references_model_schemas() {
+        let spec: Value = serde_json::from_str(json()).unwrap();
+        let schema = &spec["paths"]["/api/mars-photos/{rover}/photos"]["get"]["responses"]["200"]["content"]["application/json"]["schema"];
+
+        assert_eq!(schema["anyOf"][0]["$ref"], "#/components/schemas/MarsPhotosResponse");
+        assert_eq!(schema["anyOf"][1]["$ref"], "#/components/schemas/NormalizedMarsPhotosResponse");
+        let photo = &spec["components"]["schemas"]["MarsPhoto"]["properties"];
+        assert_eq!(photo["img_src"]["type"], "string");
+        assert!(spec["components"]["schemas"]["ErrorResponse"].is_object());
+    }
+
+    #[test]
+    fn normalized_schemas_describe_what_the_worker_serves() {
+        let spec: Value = serde_json::from_str(json()).unwrap();
+        let schemas = &spec["components"]["schemas"];
+
+        let upstream = &schemas["FlrEvent"]["properties"];
+        assert_eq!(upstream["beginTime"], json!({ "type": "string" }));
+        let normalized = &schemas["NormalizedFlrEvent"]["properties"];
+        assert_eq!(normalized["begin_time"], json!({ "type": "string", "format": "date-time" }));
+        assert!(normalized.get("beginTime").is_none());
+        assert_eq!(schemas["NormalizedDistanceData"]["properties"]["lunar"]["type"], "number");
+        assert_eq!(schemas["NormalizedNeo"]["properties"]["close_approach_data"]["items"]["$ref"], "#/components/schemas/NormalizedCloseApproach");
+
+        let cad = &spec["paths"]["/api/ssd/cad"]["get"]["responses"]["200"]["content"]["application/json"]["schema"]["anyOf"][1];
+        assert_eq!(cad["properties"]["count"]["type"], "integer");
+        assert_eq!(cad["properties"]["data"]["items"]["type"], "object");
+    }
+
+    #[test]
+    fn every_v2_route_has_an_envelope_schema() {
+        let spec: Value = serde_json::from_str(json()).unwrap();
+
+        for endpoint in registry::ENDPOINTS.iter().filter(|e| e.versions().contains(&Version::V2)) {
+            let path = endpoint.route_on(Version::V2);
+            let path = path.split('/').map(|s| s.strip_prefix(':').map_or(s.to_string(), |name| format!("{{{name}}}")));
+            let path = path.collect::<Vec<_>>().join("/");
+            let get = &spec["paths"][&path]["get"];
+            assert!(get.is_object(), "{path} is not documented");
+
+            let params = get["parameters"].as_array().unwrap();
+            assert!(params.iter().all(|p| p["name"] != "normalize"), "{path} documents normalize");
+            let envelope = &get["responses"]["200"]["content"]["application/json"]["schema"];
+            assert_eq!(envelope["required"], json!(["data", "status", "cached"]), "{path}");
+            if endpoint.paginate.is_some() {
+                let page = &envelope["properties"]["data"]["properties"];
+                for field in ["items", "page", "total_pages", "total_items"] {
+                    assert!(page.get(field).is_some(), "{path} page has no {field}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn checked_in_spec_is_up_to_date() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/openapi.yaml");
+        if std::env::var_os("UPDATE_OPENAPI").is_some() {
+            std::fs::write(path, yaml()).unwrap();
+        }
+
+        let checked_in = std::fs::read_to_string(path).unwrap();
+        assert!(
+            checked_in == yaml(),
+            "openapi.yaml is out of date; regenerate it with `UPDATE_OPENAPI=1 cargo test openapi`"
+        );
+    }
+}
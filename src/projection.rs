@@ -0,0 +1,176 @@
+//! Sparse fieldsets for `fields=`.
+//!
+//! A projection is a comma-separated list of paths, each either dotted
+//! (`photos[].camera.name`) or a JSON pointer (`/photos/camera/name`). A path
+//! that reaches an array applies to every element, so `[]` is optional; `*`
+//! matches every member of an object (e.g. the dates of the NeoWs feed in
+//! `near_earth_objects.*.name`). Paths that don't exist in a payload are
+//! skipped rather than reported, since upstream payloads vary.
+//!
+//! Projections are applied to the rendered payload, after the cache, so every
+//! projection of a request shares one cache entry.
+
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value};
+
+/// Matches every member of an object.
+const WILDCARD: &str = "*";
+
+/// A parsed `fields=` value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Projection {
+    /// The whole value is selected; set at the end of a path.
+    all: bool,
+    /// Selections below named members.
+    members: BTreeMap<String, Projection>,
+}
+
+impl Projection {
+    /// Parses a comma-separated list of dotted paths or JSON pointers. The error
+    /// reads after the parameter name, e.g. "has an empty segment in a..b".
+    pub fn parse(fields: &str) -> Result<Self, String> {
+        let mut projection = Projection::default();
+        for path in fields.split(',').map(str::trim).filter(|path| !path.is_empty()) {
+            let segments = match path.strip_prefix('/') {
+                Some(pointer) => pointer.split('/').map(|s| s.replace("~1", "/").replace("~0", "~")).collect(),
+                None => dotted(path)?,
+            };
+            if segments.iter().any(String::is_empty) {
+                return Err(format!("has an empty segment in {path}"));
+            }
+            projection.insert(&segments);
+        }
+        if projection.members.is_empty() {
+            return Err("must list at least one field".to_string());
+        }
+        Ok(projection)
+    }
+
+    fn insert(&mut self, segments: &[String]) {
+        match segments.split_first() {
+            None => self.all = true,
+            Some((first, rest)) => self.members.entry(first.clone()).or_default().insert(rest),
+        }
+    }
+
+    /// This selection combined with `other`.
+    fn merge(&self, other: &Projection) -> Projection {
+        let mut merged = self.clone();
+        merged.all |= other.all;
+        for (name, projection) in &other.members {
+            let member = match merged.members.get(name) {
+                Some(existing) => existing.merge(projection),
+                None => projection.clone(),
+            };
+            merged.members.insert(name.clone(), member);
+        }
+        merged
+    }
+
+    /// Selection for the member `name`: its own paths plus the wildcard's.
+    fn member(&self, name: &str) -> Option<Projection> {
+        match (self.members.get(name), self.members.get(WILDCARD)) {
+            (Some(named), Some(wildcard)) if name != WILDCARD => Some(named.merge(wildcard)),
+            (Some(projection), _) | (None, Some(projection)) => Some(projection.clone()),
+            (None, None) => None,
+        }
+    }
+
+    /// The selected parts of `value`: objects keep only selected members, arrays
+    /// are projected element by element. Returns `None` when nothing is selected.
+    pub fn apply(&self, value: &Value) -> Option<Value> {
+        if self.all {
+            return Some(value.clone());
+        }
+        match value {
+            Value::Object(object) => {
+                let projected: Map<String, Value> = object
+                    .iter()
+                    .filter_map(|(name, value)| Some((name.clone(), self.member(name)?.apply(value)?)))
+                    .collect();
+                Some(Value::Object(projected))
+            }
+            Value::Array(items) => Some(Value::Array(items.iter().filter_map(|item| self.apply(item)).collect())),
+            _ => None,
+        }
+    }
+}
+
+/// Segments of a dotted path, with the `[]` array markers dropped.
+fn dotted(path: &str) -> Result<Vec<String>, String> {
+    path.split('.')
+        .map(|segment| {
+            let name = segment.trim_end_matches("[]");
+            if name.contains(['[', ']']) {
+                Err(format!("has an invalid segment {segment} in {path}; only [] is supported"))
+            } else {
+                Ok(name.to_string())
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn project(fields: &str, value: Value) -> Value {
+        Projection::parse(fields).unwrap().apply(&value).unwrap()
+    }
+
+    #[test]
+    fn projects_inside_arrays() {
+        let photos = json!({"photos": [
+            {"id": 1, "img_src": "a.jpg", "camera": {"id": 20, "name": "FHAZ"}},
+            {"id": 2, "img_src": "b.jpg", "camera": {"id": 22, "name": "NAVCAM"}}
+        ]});
+
+        assert_eq!(
+            project("photos[].img_src,photos[].camera.name", photos.clone()),
+            json!({"photos": [{"img_src": "a.jpg", "camera": {"name": "FHAZ"}}, {"img_src": "b.jpg", "camera": {"name": "NAVCAM"}}]})
+        );
+        assert_eq!(project("photos.img_src, photos.camera.name", photos.clone()), project("photos[].img_src,photos[].camera.name", photos));
+    }
+
+    #[test]
+    fn accepts_json_pointers() {
+        let value = json!({"page": {"size": 20, "number": 0}, "a/b": 1, "links": {}});
+
+        assert_eq!(project("/page/size,/a~1b", value), json!({"page": {"size": 20}, "a/b": 1}));
+    }
+
+    #[test]
+    fn wildcards_match_every_member() {
+        let feed = json!({"element_count": 2, "near_earth_objects": {
+            "2024-01-02": [{"id": "1", "name": "(2024 AB)", "nasa_jpl_url": "..."}],
+            "2024-01-01": [{"id": "2", "name": "(2024 AA)", "nasa_jpl_url": "..."}]
+        }});
+
+        assert_eq!(
+            project("element_count,near_earth_objects.*.name,near_earth_objects.2024-01-01.id", feed),
+            json!({"element_count": 2, "near_earth_objects": {
+                "2024-01-02": [{"name": "(2024 AB)"}],
+                "2024-01-01": [{"id": "2", "name": "(2024 AA)"}]
+            }})
+        );
+    }
+
+    #[test]
+    fn skips_missing_paths() {
+        assert_eq!(project("title,missing.deeper,title.deeper", json!({"title": "Orion", "url": "..."})), json!({"title": "Orion"}));
+        assert_eq!(project("title", json!([{"title": "Orion"}, 7])), json!([{"title": "Orion"}]));
+    }
+
+    #[test]
+    fn rejects_malformed_lists() {
+        assert_eq!(Projection::parse(" , ").unwrap_err(), "must list at least one field");
+        assert_eq!(Projection::parse("photos..img_src").unwrap_err(), "has an empty segment in photos..img_src");
+        assert_eq!(Projection::parse("/photos//img_src").unwrap_err(), "has an empty segment in /photos//img_src");
+        assert_eq!(
+            Projection::parse("photos[0].img_src").unwrap_err(),
+            "has an invalid segment photos[0] in photos[0].img_src; only [] is supported"
+        );
+    }
+}
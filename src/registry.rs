@@ -0,0 +1,1265 @@
+//! Declarative registry of every proxied NASA endpoint.
+//!
+//! Each entry in [`ENDPOINTS`] describes one route: where it is mounted, which
+//! upstream [`Source`] serves it, how the upstream path is built, which parameters
+//! are required and how long responses are cached. Whether the NASA API key is
+//! injected follows from the entry's source. A single generic pipeline in
+//! [`crate::handlers::proxy`] serves all of them, so adding a NASA endpoint means
+//! adding one entry here.
+//!
+//! Parameters are declared with a [`Kind`] and canonicalised when a request is
+//! resolved: undeclared parameters are dropped and values are normalised (dates
+//! padded, numbers in shortest form, flags as `true`/`false`, identifiers
+//! lowercased). Equivalent requests therefore share one upstream URL and one
+//! cache key.
+//!
+//! [`Endpoint::check`] then validates the request against the same declarations:
+//! unknown parameters, missing required ones, values that aren't of their kind or
+//! outside their allowed values and range, and the endpoint's own cross-parameter
+//! rules. Every problem is reported at once in a single 400.
+//!
+//! Endpoints with a [`Normalizer`] also accept `normalize=true`, which re-emits
+//! the payload through its model (see [`crate::normalize`]), and are served on
+//! [`Version::V2`] as well. Every JSON endpoint accepts `fields=` to trim the
+//! response (see [`crate::projection`]).
+//!
+//! Endpoints with a [`Split`] serve ranges longer than the upstream allows in
+//! one call by fetching them in chunks, each cached on its own.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+
+use crate::error::{NasaApiError, Problem, Result};
+use crate::middleware::auth::API_KEY_PARAM;
+use crate::models::{apod, donki, epic, mars, neo, ssd};
+use crate::neo_feed;
+use crate::normalize::{self, Normalizer};
+use crate::projection::Projection;
+use crate::upstream::{self, Source};
+
+/// How the upstream payload is decoded and returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// JSON passed through (and cached) as-is.
+    Json,
+    /// Binary payload streamed back with the upstream content type; never cached.
+    Binary,
+    /// Exoplanet Archive TAP output: JSON when `format=json`, otherwise the text
+    /// body wrapped in `{"format": ..., "data": ...}`.
+    Tap,
+}
+
+/// API surface an endpoint is served on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Version {
+    /// `/api/...`: the upstream payload as-is, byte for byte.
+    V1,
+    /// `/api/v2/...`: the normalized payload in an [`crate::models::ApiResponse`]
+    /// envelope, paged lists as a
+    /// [`crate::models::PaginatedResponse`].
+    V2,
+}
+
+impl Version {
+    const V2_PREFIX: &'static str = "/api/v2/";
+
+    /// The v1 path of a request path, e.g. `/api/apod` for `/api/v2/apod`, so key
+    /// scopes and route quotas apply to both surfaces alike.
+    pub fn unversioned(path: &str) -> Cow<'_, str> {
+        match path.strip_prefix(Self::V2_PREFIX) {
+            Some(rest) => Cow::Owned(format!("/api/{rest}")),
+            None => Cow::Borrowed(path),
+        }
+    }
+}
+
+/// Which client query parameters are forwarded upstream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Query {
+    /// Forward only the listed parameters.
+    Only(&'static [Param]),
+    /// Forward nothing; the route is fully described by its path.
+    None,
+}
+
+/// How a parameter value is canonicalised. Values that don't parse as their
+/// kind are kept as sent (trimmed) and left to validation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Text,
+    /// `YYYY-MM-DD`, e.g. `2024-1-5` becomes `2024-01-05`.
+    Date,
+    /// Whole number, e.g. `007` becomes `7`.
+    Integer,
+    /// Decimal number in its shortest form, e.g. `29.780` becomes `29.78`.
+    Number,
+    /// `true`/`false`, also accepting `1`/`0` and `yes`/`no` in any case.
+    Flag,
+    /// Case-insensitive identifier, lowercased.
+    Lowercase,
+}
+
+impl Kind {
+    /// What a well-formed value looks like, for validation messages.
+    fn expected(self) -> &'static str {
+        match self {
+            Kind::Text | Kind::Lowercase => "text",
+            Kind::Date => "a date in YYYY-MM-DD format",
+            Kind::Integer => "a whole number",
+            Kind::Number => "a number",
+            Kind::Flag => "true or false",
+        }
+    }
+}
+
+/// Query parameters the proxy reads itself: accepted on every route, never
+/// forwarded upstream and not part of the cache key.
+pub const PROXY_PARAMS: &[Param] = &[text(API_KEY_PARAM), flag(NORMALIZE_PARAM), text(FIELDS_PARAM)];
+
+/// Asks for the payload re-emitted through the endpoint's model.
+pub const NORMALIZE_PARAM: &str = "normalize";
+
+/// Lists the parts of the payload to return, as a [`Projection`].
+pub const FIELDS_PARAM: &str = "fields";
+
+/// A declared query or path parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Param {
+    pub name: &'static str,
+    pub kind: Kind,
+    /// Allowed values after canonicalisation; empty allows any.
+    pub values: &'static [&'static str],
+    /// Inclusive bounds for numeric kinds.
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+}
+
+impl Param {
+    /// Restricts the value to one of `values`.
+    const fn one_of(self, values: &'static [&'static str]) -> Self {
+        Self { values, ..self }
+    }
+
+    /// Restricts a numeric value to `min..=max`.
+    const fn between(self, min: i64, max: i64) -> Self {
+        Self { min: Some(min), max: Some(max), ..self }
+    }
+
+    /// Restricts a numeric value to `min` or more.
+    const fn at_least(self, min: i64) -> Self {
+        Self { min: Some(min), ..self }
+    }
+
+    /// What is wrong with a canonicalised `value`, if anything.
+    pub fn problem(&self, value: &str) -> Option<String> {
+        let well_formed = match self.kind {
+            Kind::Text | Kind::Lowercase => true,
+            Kind::Date => NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok(),
+            Kind::Integer => value.parse::<i64>().is_ok(),
+            Kind::Number => value.parse::<f64>().is_ok_and(f64::is_finite),
+            Kind::Flag => matches!(value, "true" | "false"),
+        };
+        if !well_formed {
+            return Some(format!("must be {}", self.kind.expected()));
+        }
+        if !self.values.is_empty() && !self.values.contains(&value) {
+            return Some(format!("must be one of: {}", self.values.join(", ")));
+        }
+
+        let number = match self.kind {
+            Kind::Integer | Kind::Number => value.parse::<f64>().ok()?,
+            _ => return None,
+        };
+        match (self.min, self.max) {
+            (Some(min), Some(max)) if !(min as f64..=max as f64).contains(&number) => Some(format!("must be between {min} and {max}")),
+            (Some(min), None) if number < min as f64 => Some(format!("must be at least {min}")),
+            _ => None,
+        }
+    }
+
+    /// Canonical form of `value`.
+    pub fn canonicalize(&self, value: &str) -> String {
+        let value = value.trim();
+        let canonical = match self.kind {
+            Kind::Text => None,
+            Kind::Date => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .map(|date| date.format("%Y-%m-%d").to_string()),
+            Kind::Integer => value.parse::<i64>().ok().map(|n| n.to_string()),
+            Kind::Number => value
+                .parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite())
+                .map(|n| if n == 0.0 { 0.0 } else { n }.to_string()),
+            Kind::Flag => match value.to_ascii_lowercase().as_str() {
+                "true" | "1" | "yes" => Some("true".to_string()),
+                "false" | "0" | "no" => Some("false".to_string()),
+                _ => None,
+            },
+            Kind::Lowercase => Some(value.to_ascii_lowercase()),
+        };
+        canonical.unwrap_or_else(|| value.to_string())
+    }
+}
+
+const fn param(name: &'static str, kind: Kind) -> Param {
+    Param { name, kind, values: &[], min: None, max: None }
+}
+
+const fn text(name: &'static str) -> Param {
+    param(name, Kind::Text)
+}
+
+const fn date(name: &'static str) -> Param {
+    param(name, Kind::Date)
+}
+
+const fn integer(name: &'static str) -> Param {
+    param(name, Kind::Integer)
+}
+
+const fn number(name: &'static str) -> Param {
+    param(name, Kind::Number)
+}
+
+const fn flag(name: &'static str) -> Param {
+    param(name, Kind::Flag)
+}
+
+const fn lowercase(name: &'static str) -> Param {
+    param(name, Kind::Lowercase)
+}
+
+/// Fan-out for requests spanning more than the upstream serves in one call. Each
+/// chunk is fetched and cached as a request of its own, and the payloads are
+/// merged before rendering.
+#[derive(Debug, Clone, Copy)]
+pub struct Split {
+    /// Environment variable overriding [`Split::default_limit`].
+    pub limit_var: &'static str,
+    /// Largest span a request may cover, in the unit of [`Split::check`].
+    pub default_limit: i64,
+    /// Ceiling for a configured limit, keeping the chunks of one request within
+    /// the worker's subrequest budget.
+    pub max_limit: i64,
+    /// Reports a request spanning more than the limit; run by [`Endpoint::check`].
+    pub check: fn(&ProxyRequest, i64, &mut Vec<Problem>),
+    /// The requests covering a checked request, in order; the request itself
+    /// when the upstream serves it in one call.
+    pub chunks: fn(&ProxyRequest) -> Vec<ProxyRequest>,
+    /// Combines the chunks' payloads, in the order of their requests.
+    pub merge: fn(Vec<serde_json::Value>) -> serde_json::Value,
+}
+
+impl Split {
+    /// The limit for a configured value of [`Split::limit_var`]: the default
+    /// when unset or invalid, and at most [`Split::max_limit`]. The message
+    /// says why a configured value wasn't used as is.
+    pub fn limit(&self, configured: Option<&str>) -> (i64, Option<String>) {
+        let Some(configured) = configured else {
+            return (self.default_limit, None);
+        };
+        match configured.trim().parse::<i64>() {
+            Ok(limit) if limit > self.max_limit => (
+                self.max_limit,
+                Some(format!("Clamping {} to {}: {} is over the subrequest budget", self.limit_var, self.max_limit, limit)),
+            ),
+            Ok(limit) if limit > 0 => (limit, None),
+            _ => (
+                self.default_limit,
+                Some(format!("Ignoring {}: not a positive integer: {}", self.limit_var, configured)),
+            ),
+        }
+    }
+}
+
+/// Parameters of a request path matching the route pattern `route`, where
+/// `:name` segments match any non-empty segment; `None` when it doesn't match.
+/// A trailing slash is ignored.
+pub fn route_params(route: &str, path: &str) -> Option<HashMap<String, String>> {
+    let mut params = HashMap::new();
+    let mut route = route.split('/');
+    let trimmed = path.trim_end_matches('/');
+    let mut path = if trimmed.is_empty() { path } else { trimmed }.split('/');
+    loop {
+        match (route.next(), path.next()) {
+            (None, None) => return Some(params),
+            (Some(expected), Some(actual)) if expected == actual => {}
+            (Some(expected), Some(actual)) if expected.starts_with(':') && !actual.is_empty() => {
+                params.insert(expected[1..].to_string(), actual.to_string());
+            }
+            _ => return None,
+        }
+    }
+}
+
+/// Declaration of a single proxied endpoint.
+#[derive(Debug)]
+pub struct Endpoint {
+    /// Route pattern registered with the worker router, e.g. `/api/neo/:asteroid_id`.
+    pub route: &'static str,
+    /// Upstream data source.
+    pub source: &'static Source,
+    /// Upstream path relative to the source base URL; `{name}` placeholders are
+    /// filled from the matching `:name` route parameter.
+    pub upstream_path: &'static str,
+    /// Query parameters forwarded upstream.
+    pub query: Query,
+    /// Query parameters the worker applies itself in [`Endpoint::refine`]; like
+    /// [`PROXY_PARAMS`], they are not forwarded and not part of the cache key.
+    pub local: &'static [Param],
+    /// Route parameters that need canonicalising; others are taken as text.
+    pub path: &'static [Param],
+    /// Query parameters that must be present.
+    pub required: &'static [&'static str],
+    /// Defaults applied when the client omits a parameter.
+    pub defaults: &'static [(&'static str, &'static str)],
+    /// Cache lifetime in minutes.
+    pub ttl_minutes: i64,
+    pub format: Format,
+    /// Re-emits the payload through its model for `normalize=true`; endpoints
+    /// without one reject the parameter.
+    pub normalize: Option<Normalizer>,
+    /// Builds the [`crate::models::PaginatedResponse`] served on [`Version::V2`] for paged
+    /// lists, in place of [`Endpoint::normalize`].
+    pub paginate: Option<Normalizer>,
+    /// Reshapes the (normalized) payload according to [`Endpoint::local`].
+    pub refine: Option<fn(&ProxyRequest, serde_json::Value) -> serde_json::Value>,
+    /// Cross-parameter rules, run after each parameter has been checked on its own.
+    pub validate: Option<fn(&ProxyRequest, &mut Vec<Problem>)>,
+    /// Fans out requests the upstream can't serve in one call.
+    pub split: Option<Split>,
+}
+
+/// Parameters of one incoming request, resolved against an [`Endpoint`].
+#[derive(Debug, Clone, Default)]
+pub struct ProxyRequest {
+    /// Route parameters, keyed by name.
+    pub path: HashMap<String, String>,
+    /// Query parameters after defaults have been applied.
+    pub query: Vec<(String, String)>,
+    /// Query parameters the endpoint doesn't declare, reported by [`Endpoint::check`].
+    pub unknown: Vec<String>,
+    /// [`PROXY_PARAMS`] other than the API key and the endpoint's
+    /// [`Endpoint::local`] parameters, canonicalised and sorted by name.
+    pub options: Vec<(String, String)>,
+}
+
+impl ProxyRequest {
+    /// Resolves a request into canonical form: undeclared parameters and
+    /// [`PROXY_PARAMS`] (including the client's own API key, which must never be
+    /// forwarded or cached) are dropped, defaults filled in, values canonicalised
+    /// and the query sorted by name. The names of undeclared parameters are kept
+    /// in [`ProxyRequest::unknown`], the proxy's own options in
+    /// [`ProxyRequest::options`].
+    pub fn new(endpoint: &Endpoint, mut path: HashMap<String, String>, mut query: Vec<(String, String)>) -> Self {
+        for (name, value) in path.iter_mut() {
+            if let Some(param) = endpoint.path.iter().find(|p| p.name == name) {
+                *value = param.canonicalize(value);
+            }
+        }
+
+        let mut options = Vec::new();
+        query.retain(|(name, value)| {
+            let Some(param) = PROXY_PARAMS.iter().chain(endpoint.local).find(|p| p.name == name) else {
+                return true;
+            };
+            let value = param.canonicalize(value);
+            if param.name != API_KEY_PARAM && !value.is_empty() && !options.iter().any(|(k, _)| k == name) {
+                options.push((name.clone(), value));
+            }
+            false
+        });
+        options.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let declared = endpoint.query_params();
+        let mut unknown = Vec::new();
+        let mut query: Vec<(String, String)> = query
+            .into_iter()
+            .filter_map(|(name, value)| {
+                let Some(param) = declared.iter().find(|p| p.name == name) else {
+                    if !unknown.contains(&name) {
+                        unknown.push(name);
+                    }
+                    return None;
+                };
+                let value = param.canonicalize(&value);
+                (!value.is_empty()).then_some((name, value))
+            })
+            .collect();
+        for (key, value) in endpoint.defaults {
+            if !query.iter().any(|(k, _)| k == key) {
+                query.push((key.to_string(), value.to_string()));
+            }
+        }
+        query.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Self { path, query, unknown, options }
+    }
+
+    /// Returns the first value of a query parameter.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.query.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+
+    /// Returns the value of one of [`PROXY_PARAMS`] or [`Endpoint::local`].
+    pub fn option(&self, name: &str) -> Option<&str> {
+        self.options.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+
+    /// Whether the client asked for a normalized response.
+    pub fn normalized(&self) -> bool {
+        self.option(NORMALIZE_PARAM) == Some("true")
+    }
+
+    /// The client's `fields=` projection, if it sent one.
+    pub fn projection(&self) -> Option<std::result::Result<Projection, String>> {
+        self.option(FIELDS_PARAM).map(Projection::parse)
+    }
+}
+
+impl Endpoint {
+    /// Surfaces the endpoint is served on: v2 needs a model to normalize through.
+    pub fn versions(&self) -> &'static [Version] {
+        if self.normalize.is_some() {
+            &[Version::V1, Version::V2]
+        } else {
+            &[Version::V1]
+        }
+    }
+
+    /// Route pattern on a surface, e.g. `/api/v2/neo/:asteroid_id`.
+    pub fn route_on(&self, version: Version) -> String {
+        match version {
+            Version::V1 => self.route.to_string(),
+            Version::V2 => format!("{}{}", Version::V2_PREFIX, self.route.trim_start_matches("/api/")),
+        }
+    }
+
+    /// The payload served on `version`, or `None` when it is the upstream one.
+    pub fn normalizer(&self, version: Version, request: &ProxyRequest) -> Option<Normalizer> {
+        match version {
+            Version::V1 => self.normalize.filter(|_| request.normalized()),
+            Version::V2 => self.paginate.or(self.normalize),
+        }
+    }
+
+    /// Declared query parameters.
+    pub fn query_params(&self) -> &'static [Param] {
+        match self.query {
+            Query::Only(params) => params,
+            Query::None => &[],
+        }
+    }
+
+    /// Names of the `:param` segments in [`Endpoint::route`].
+    pub fn route_params(&self) -> impl Iterator<Item = &'static str> {
+        self.route.split('/').filter_map(|segment| segment.strip_prefix(':'))
+    }
+
+    /// Whether a request path such as `/api/neo/3542519` matches [`Endpoint::route`].
+    pub fn matches(&self, path: &str) -> bool {
+        self.path_params(path).is_some()
+    }
+
+    /// Route parameters of a matching request path, e.g. `asteroid_id = 3542519`
+    /// for `/api/neo/3542519`.
+    pub fn path_params(&self, path: &str) -> Option<HashMap<String, String>> {
+        route_params(self.route, path)
+    }
+
+    /// Validates a request against the declared parameters and the endpoint's
+    /// own rules, collecting every problem into one [`NasaApiError::Validation`].
+    /// `limit` is the configured span limit of the endpoint's [`Split`], if it
+    /// has one; its default applies otherwise.
+    pub fn check(&self, request: &ProxyRequest, limit: Option<i64>) -> Result<()> {
+        let mut problems: Vec<Problem> = request
+            .unknown
+            .iter()
+            .map(|name| Problem::new(name, "is not a parameter of this endpoint"))
+            .collect();
+        for name in self.required {
+            if request.param(name).is_none() {
+                problems.push(Problem::new(*name, "is required"));
+            }
+        }
+
+        let path = self.path.iter().filter_map(|param| Some((param, request.path.get(param.name)?.as_str())));
+        let query = self.query_params().iter().filter_map(|param| Some((param, request.param(param.name)?)));
+        let options = PROXY_PARAMS.iter().chain(self.local).filter_map(|param| Some((param, request.option(param.name)?)));
+        for (param, value) in path.chain(query).chain(options) {
+            if let Some(message) = param.problem(value) {
+                problems.push(Problem::new(param.name, message));
+            }
+        }
+        if request.normalized() && self.normalize.is_none() {
+            problems.push(Problem::new(NORMALIZE_PARAM, "is not supported by this endpoint"));
+        }
+        match request.projection() {
+            Some(_) if self.format == Format::Binary => problems.push(Problem::new(FIELDS_PARAM, "is not supported by this endpoint")),
+            Some(Err(message)) => problems.push(Problem::new(FIELDS_PARAM, message)),
+            _ => {}
+        }
+
+        if let Some(validate) = self.validate {
+            validate(request, &mut problems);
+        }
+        if let Some(split) = self.split {
+            (split.check)(request, limit.unwrap_or(split.default_limit), &mut problems);
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(NasaApiError::Validation(problems))
+        }
+    }
+
+    /// Upstream path with route parameters substituted and percent-encoded.
+    pub fn upstream_path(&self, request: &ProxyRequest) -> String {
+        let mut path = self.upstream_path.to_string();
+        for (name, value) in &request.path {
+            path = path.replace(&format!("{{{name}}}"), &urlencoding::encode(value));
+        }
+        path
+    }
+
+    /// Query parameters forwarded upstream.
+    pub fn upstream_query(&self, request: &ProxyRequest) -> Vec<(String, String)> {
+        match self.query {
+            Query::Only(_) => request.query.clone(),
+            Query::None => Vec::new(),
+        }
+    }
+
+    /// Cache key: the resolved route path without the `/api/` prefix, followed by
+    /// the forwarded query parameters sorted by name.
+    pub fn cache_key(&self, request: &ProxyRequest) -> String {
+        let mut path = self.route.trim_start_matches("/api/").to_string();
+        for (name, value) in &request.path {
+            path = path.replace(&format!(":{name}"), value);
+        }
+        crate::cache::get_cache_key(&path, &self.upstream_query(request))
+    }
+}
+
+/// Cameras of each rover, as accepted by the `camera` parameter.
+const CAMERAS: &[(&str, &[&str])] = &[
+    ("curiosity", &["fhaz", "rhaz", "mast", "chemcam", "mahli", "mardi", "navcam"]),
+    ("opportunity", &["fhaz", "rhaz", "navcam", "pancam", "minites"]),
+    ("spirit", &["fhaz", "rhaz", "navcam", "pancam", "minites"]),
+];
+
+/// The `name` parameter parsed as a date, if present and well-formed.
+fn date_param(request: &ProxyRequest, name: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(request.param(name)?, "%Y-%m-%d").ok()
+}
+
+/// Reports `end` when it is before `start`; both must be well-formed dates.
+fn check_date_order(request: &ProxyRequest, start: &str, end: &str, problems: &mut Vec<Problem>) {
+    if let (Some(from), Some(to)) = (date_param(request, start), date_param(request, end)) {
+        if to < from {
+            problems.push(Problem::new(end, format!("must not be before {start}")));
+        }
+    }
+}
+
+/// Reports each of `names` present alongside `name`.
+fn check_exclusive(request: &ProxyRequest, name: &str, names: &[&str], problems: &mut Vec<Problem>) {
+    if request.param(name).is_none() {
+        return;
+    }
+    for other in names.iter().filter(|other| request.param(other).is_some()) {
+        problems.push(Problem::new(name, format!("cannot be combined with {other}")));
+    }
+}
+
+fn validate_apod(request: &ProxyRequest, problems: &mut Vec<Problem>) {
+    check_exclusive(request, "count", &["date", "start_date", "end_date"], problems);
+    check_exclusive(request, "date", &["start_date", "end_date"], problems);
+    if request.param("end_date").is_some() && request.param("start_date").is_none() {
+        problems.push(Problem::new("start_date", "is required with end_date"));
+    }
+    check_date_order(request, "start_date", "end_date", problems);
+}
+
+fn validate_neo_feed(request: &ProxyRequest, problems: &mut Vec<Problem>) {
+    check_date_order(request, "start_date", "end_date", problems);
+    let distance = |name| request.option(name)?.parse::<f64>().ok();
+    if let (Some(min), Some(max)) = (distance("min_distance"), distance("max_distance")) {
+        if max < min {
+            problems.push(Problem::new("max_distance", "must not be less than min_distance"));
+        }
+    }
+}
+
+fn validate_donki(request: &ProxyRequest, problems: &mut Vec<Problem>) {
+    check_date_order(request, "startDate", "endDate", problems);
+}
+
+fn validate_rover_photos(request: &ProxyRequest, problems: &mut Vec<Problem>) {
+    match (request.param("sol").is_some(), request.param("earth_date").is_some()) {
+        (false, false) => problems.push(Problem::new("sol", "or earth_date is required")),
+        (true, true) => problems.push(Problem::new("sol", "cannot be combined with earth_date")),
+        _ => {}
+    }
+
+    let rover = request.path.get("rover").map(String::as_str);
+    let cameras = CAMERAS.iter().find(|(name, _)| Some(*name) == rover);
+    if let (Some((rover, cameras)), Some(camera)) = (cameras, request.param("camera")) {
+        if !cameras.contains(&camera) {
+            problems.push(Problem::new("camera", format!("must be one of the {rover} cameras: {}", cameras.join(", "))));
+        }
+    }
+}
+
+/// Shorthand for the common case: JSON, the declared query parameters forwarded,
+/// no extra checks.
+const fn json(
+    route: &'static str,
+    source: &'static Source,
+    upstream_path: &'static str,
+    params: &'static [Param],
+    ttl_minutes: i64,
+) -> Endpoint {
+    Endpoint {
+        route,
+        source,
+        upstream_path,
+        query: Query::Only(params),
+        local: &[],
+        path: &[],
+        required: &[],
+        defaults: &[],
+        ttl_minutes,
+        format: Format::Json,
+        normalize: None,
+        paginate: None,
+        refine: None,
+        validate: None,
+        split: None,
+    }
+}
+
+/// Shorthand for DONKI endpoints, which all take an ordered date range and
+/// return a list of events.
+const fn donki(
+    route: &'static str,
+    upstream_path: &'static str,
+    params: &'static [Param],
+    normalize: Normalizer,
+    ttl_minutes: i64,
+) -> Endpoint {
+    Endpoint {
+        normalize: Some(normalize),
+        validate: Some(validate_donki),
+        ..json(route, &upstream::NASA, upstream_path, params, ttl_minutes)
+    }
+}
+
+/// Shorthand for routes fully identified by their path parameters.
+const fn path_only(route: &'static str, source: &'static Source, upstream_path: &'static str, ttl_minutes: i64) -> Endpoint {
+    Endpoint { query: Query::None, ..json(route, source, upstream_path, &[], ttl_minutes) }
+}
+
+const NEO_FEED_REFINEMENTS: &[Param] = &[
+    flag("hazardous"),
+    lowercase("size").one_of(neo_feed::SIZES),
+    number("min_distance").at_least(0),
+    number("max_distance").at_least(0),
+    lowercase("sort").one_of(neo_feed::SORT_KEYS),
+    flag("flatten"),
+];
+const DONKI: &[Param] = &[date("startDate"), date("endDate")];
+const ROVER: &[Param] = &[lowercase("rover").one_of(&["curiosity", "opportunity", "spirit"])];
+const EPIC_DATE: &[Param] = &[date("date")];
+const TECH_TRANSFER: &[Param] = &[text("query"), text("center"), integer("year"), text("word")];
+const COORDINATES: &[Param] = &[number("lat").between(-90, 90), number("lon").between(-180, 180), date("date"), number("dim")];
+
+/// Every proxied endpoint served by the worker.
+pub static ENDPOINTS: &[Endpoint] = &[
+    // APOD (Astronomy Picture of the Day) - updates daily
+    Endpoint {
+        defaults: &[("thumbs", "false")],
+        normalize: Some(normalize::one_or_list::<apod::ApodResponse>()),
+        validate: Some(validate_apod),
+        ..json(
+            "/api/apod",
+            &upstream::NASA,
+            "/planetary/apod",
+            &[date("date"), date("start_date"), date("end_date"), integer("count").between(1, 100), flag("thumbs")],
+            1440,
+        )
+    },
+
+    // NeoWs (Near Earth Objects)
+    Endpoint {
+        local: NEO_FEED_REFINEMENTS,
+        normalize: Some(normalize::one::<neo::NeoFeedResponse>()),
+        refine: Some(neo_feed::refine),
+        validate: Some(validate_neo_feed),
+        split: Some(Split {
+            limit_var: "NEO_FEED_MAX_DAYS",
+            default_limit: neo_feed::MAX_DAYS,
+            max_limit: neo_feed::MAX_DAYS_CEILING,
+            check: neo_feed::check_range,
+            chunks: neo_feed::chunks,
+            merge: neo_feed::merge,
+        }),
+        ..json("/api/neo/feed", &upstream::NASA, "/neo/rest/v1/feed", &[date("start_date"), date("end_date")], 60)
+    },
+    Endpoint {
+        defaults: &[("page", "0"), ("size", "20")],
+        normalize: Some(normalize::one::<neo::NeoBrowseResponse>()),
+        paginate: Some(normalize::paginated::<neo::NeoBrowseResponse, neo::Neo>()),
+        ..json(
+            "/api/neo/browse",
+            &upstream::NASA,
+            "/neo/rest/v1/neo/browse",
+            &[integer("page").at_least(0), integer("size").between(1, 20)],
+            360,
+        )
+    },
+    Endpoint {
+        normalize: Some(normalize::one::<neo::Neo>()),
+        ..path_only("/api/neo/:asteroid_id", &upstream::NASA, "/neo/rest/v1/neo/{asteroid_id}", 360)
+    },
+
+    // DONKI (Space Weather)
+    donki("/api/donki/cme", "/DONKI/CME", DONKI, normalize::list::<donki::CmeEvent>(), 30),
+    donki(
+        "/api/donki/cme-analysis",
+        "/DONKI/CMEAnalysis",
+        &[
+            date("startDate"),
+            date("endDate"),
+            flag("mostAccurateOnly"),
+            flag("completeEntryOnly"),
+            integer("speed"),
+            integer("halfAngle"),
+            text("catalog"),
+            text("keyword"),
+        ],
+        normalize::list::<donki::CmeAnalysis>(),
+        30,
+    ),
+    donki("/api/donki/gst", "/DONKI/GST", DONKI, normalize::list::<donki::GstEvent>(), 30),
+    donki(
+        "/api/donki/ips",
+        "/DONKI/IPS",
+        &[date("startDate"), date("endDate"), text("location"), text("catalog")],
+        normalize::list::<donki::IpsEvent>(),
+        30,
+    ),
+    donki("/api/donki/flr", "/DONKI/FLR", DONKI, normalize::list::<donki::FlrEvent>(), 30),
+    donki("/api/donki/sep", "/DONKI/SEP", DONKI, normalize::list::<donki::SepEvent>(), 30),
+    donki("/api/donki/mpc", "/DONKI/MPC", DONKI, normalize::list::<donki::MpcEvent>(), 30),
+    donki("/api/donki/rbe", "/DONKI/RBE", DONKI, normalize::list::<donki::RbeEvent>(), 30),
+    donki("/api/donki/hss", "/DONKI/HSS", DONKI, normalize::list::<donki::HssEvent>(), 30),
+    donki("/api/donki/wsa-enlil", "/DONKI/WSAEnlilSimulations", DONKI, normalize::list::<donki::WsaEnlilSimulation>(), 30),
+    donki(
+        "/api/donki/notifications",
+        "/DONKI/notifications",
+        &[date("startDate"), date("endDate"), text("type").one_of(&["all", "FLR", "SEP", "CME", "IPS", "MPC", "GST", "RBE", "report"])],
+        normalize::list::<donki::Notification>(),
+        15,
+    ),
+
+    // Earth Imagery - imagery is binary and is not cached
+    Endpoint {
+        required: &["lat", "lon"],
+        format: Format::Binary,
+        ..json("/api/earth/imagery", &upstream::NASA, "/planetary/earth/imagery", COORDINATES, 0)
+    },
+    Endpoint {
+        required: &["lat", "lon", "date"],
+        ..json("/api/earth/assets", &upstream::NASA, "/planetary/earth/assets", COORDINATES, 1440)
+    },
+
+    // EPIC
+    Endpoint {
+        normalize: Some(normalize::list::<epic::EpicDate>()),
+        ..path_only("/api/epic/natural/all", &upstream::EPIC, "/natural/all", 360)
+    },
+    Endpoint {
+        path: EPIC_DATE,
+        normalize: Some(normalize::list::<epic::EpicImage>()),
+        ..path_only("/api/epic/natural/date/:date", &upstream::EPIC, "/natural/date/{date}", 1440)
+    },
+    Endpoint {
+        normalize: Some(normalize::list::<epic::EpicDate>()),
+        ..path_only("/api/epic/enhanced/all", &upstream::EPIC, "/enhanced/all", 360)
+    },
+    Endpoint {
+        path: EPIC_DATE,
+        normalize: Some(normalize::list::<epic::EpicImage>()),
+        ..path_only("/api/epic/enhanced/date/:date", &upstream::EPIC, "/enhanced/date/{date}", 1440)
+    },
+
+    // Mars Rover Photos
+    Endpoint {
+        path: ROVER,
+        normalize: Some(normalize::one::<mars::MarsPhotosResponse>()),
+        validate: Some(validate_rover_photos),
+        ..json(
+            "/api/mars-photos/:rover/photos",
+            &upstream::NASA,
+            "/mars-photos/api/v1/rovers/{rover}/photos",
+            &[integer("sol").at_least(0), date("earth_date"), lowercase("camera"), integer("page").at_least(1)],
+            360,
+        )
+    },
+    Endpoint {
+        path: ROVER,
+        normalize: Some(normalize::one::<mars::MarsPhotosResponse>()),
+        ..path_only("/api/mars-photos/:rover/latest", &upstream::NASA, "/mars-photos/api/v1/rovers/{rover}/latest_photos", 60)
+    },
+    Endpoint {
+        path: ROVER,
+        normalize: Some(normalize::one::<mars::MarsManifest>()),
+        ..path_only("/api/mars-photos/manifests/:rover", &upstream::NASA, "/mars-photos/api/v1/manifests/{rover}", 1440)
+    },
+
+    // Tech Transfer - data doesn't change frequently
+    json("/api/techtransfer/patents", &upstream::NASA, "/techtransfer/patent/", TECH_TRANSFER, 10080),
+    json("/api/techtransfer/patents-issued", &upstream::NASA, "/techtransfer/patent_issued/", TECH_TRANSFER, 10080),
+    json("/api/techtransfer/software", &upstream::NASA, "/techtransfer/software/", TECH_TRANSFER, 10080),
+    json("/api/techtransfer/spinoffs", &upstream::NASA, "/techtransfer/spinoff/", TECH_TRANSFER, 10080),
+
+    // NASA Image and Video Library
+    Endpoint {
+        defaults: &[("page", "1")],
+        ..json(
+            "/api/media/search",
+            &upstream::MEDIA,
+            "/search",
+            &[
+                text("q"),
+                text("center"),
+                text("description"),
+                text("keywords"),
+                text("location"),
+                lowercase("media_type"),
+                text("nasa_id"),
+                integer("page").at_least(1),
+                integer("page_size").at_least(1),
+                text("photographer"),
+                text("title"),
+                integer("year_start"),
+                integer("year_end"),
+            ],
+            360,
+        )
+    },
+    path_only("/api/media/asset/:nasa_id", &upstream::MEDIA, "/asset/{nasa_id}", 1440),
+    path_only("/api/media/metadata/:nasa_id", &upstream::MEDIA, "/metadata/{nasa_id}", 1440),
+    path_only("/api/media/captions/:nasa_id", &upstream::MEDIA, "/captions/{nasa_id}", 1440),
+
+    // Exoplanet Archive
+    Endpoint {
+        required: &["query"],
+        defaults: &[("format", "json")],
+        format: Format::Tap,
+        ..json("/api/exoplanets/query", &upstream::EXOPLANET_ARCHIVE, "/sync", &[text("query"), lowercase("format").one_of(&["json", "csv", "tsv", "votable"])], 1440)
+    },
+
+    // SSD/CNEOS (Solar System Dynamics)
+    Endpoint {
+        normalize: Some(normalize::table::<ssd::CadResponse>()),
+        ..json(
+            "/api/ssd/cad",
+            &upstream::SSD,
+            "/cad.api",
+            &[
+                date("date-min"),
+                date("date-max"),
+                text("dist-min"),
+                text("dist-max"),
+                number("h-min"),
+                number("h-max"),
+                number("v-inf-min"),
+                number("v-inf-max"),
+                flag("pha"),
+                flag("nea"),
+                flag("comet"),
+                flag("nea-comet"),
+                flag("neo"),
+                text("des"),
+                text("spk"),
+                lowercase("body"),
+                text("sort"),
+                integer("limit"),
+                flag("fullname"),
+                flag("diameter"),
+            ],
+            60,
+        )
+    },
+    Endpoint {
+        required: &["sstr"],
+        ..json(
+            "/api/ssd/sbdb",
+            &upstream::SSD,
+            "/sbdb.api",
+            &[text("sstr"), text("des"), text("spk"), flag("cad"), flag("ca-data"), flag("phys-par"), flag("discovery"), flag("full-prec")],
+            360,
+        )
+    },
+    json(
+        "/api/ssd/sentry",
+        &upstream::SSD,
+        "/sentry.api",
+        &[text("des"), text("spk"), flag("all"), flag("removed"), number("ip-min"), number("ps-min"), number("h-max"), integer("days")],
+        60,
+    ),
+    Endpoint {
+        required: &["tdes"],
+        ..json("/api/ssd/scout", &upstream::SSD, "/scout.api", &[text("tdes")], 30)
+    },
+    json(
+        "/api/ssd/nhats",
+        &upstream::SSD,
+        "/nhats.api",
+        &[integer("dv"), integer("dur"), integer("stay"), text("launch"), number("h"), number("h-max"), integer("occ"), text("des"), text("spk")],
+        1440,
+    ),
+    Endpoint {
+        normalize: Some(normalize::table::<ssd::FireballResponse>()),
+        ..json(
+            "/api/ssd/fireballs",
+            &upstream::SSD,
+            "/fireball.api",
+            &[
+                date("date-min"),
+                date("date-max"),
+                number("energy-min"),
+                number("energy-max"),
+                number("impact-e-min"),
+                number("impact-e-max"),
+                number("vel-min"),
+                number("vel-max"),
+                number("alt-min"),
+                number("alt-max"),
+                flag("req-loc"),
+                flag("req-alt"),
+                flag("req-vel"),
+                flag("req-vel-comp"),
+                flag("vel-comp"),
+                text("sort"),
+                integer("limit"),
+            ],
+            60,
+        )
+    },
+];
+
+/// Looks up an endpoint by its route pattern.
+#[cfg(test)]
+pub fn find(route: &str) -> Option<&'static Endpoint> {
+    ENDPOINTS.iter().find(|e| e.route == route)
+}
+
+/// Finds the endpoint serving a request path, preferring static segments over
+/// parameters the same way the router does (`/api/neo/browse` is not an asteroid id).
+pub fn resolve(path: &str) -> Option<&'static Endpoint> {
+    ENDPOINTS
+        .iter()
+        .filter(|e| e.matches(path))
+        .min_by_key(|e| e.route_params().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(route: &str, path: &[(&str, &str)], query: &[(&str, &str)]) -> (&'static Endpoint, ProxyRequest) {
+        let endpoint = find(route).unwrap();
+        let path = path.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        let query = query.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        (endpoint, ProxyRequest::new(endpoint, path, query))
+    }
+
+    #[test]
+    fn routes_are_unique_and_templates_match_route_params() {
+        for (i, endpoint) in ENDPOINTS.iter().enumerate() {
+            assert!(ENDPOINTS[i + 1..].iter().all(|e| e.route != endpoint.route), "duplicate route {}", endpoint.route);
+            for name in endpoint.route_params() {
+                assert!(
+                    endpoint.upstream_path.contains(&format!("{{{name}}}")),
+                    "{} does not use :{name} upstream",
+                    endpoint.route
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn substitutes_and_encodes_path_params() {
+        let (endpoint, req) = request("/api/media/asset/:nasa_id", &[("nasa_id", "as11 40")], &[("q", "ignored")]);
+
+        assert_eq!(endpoint.upstream_path(&req), "/asset/as11%2040");
+        assert!(endpoint.upstream_query(&req).is_empty());
+        assert_eq!(endpoint.cache_key(&req), "media/asset/as11 40:");
+    }
+
+    #[test]
+    fn cache_key_sorts_forwarded_params() {
+        let (endpoint, req) = request("/api/donki/cme", &[], &[("startDate", "2024-01-01"), ("endDate", "2024-01-07")]);
+
+        assert_eq!(endpoint.cache_key(&req), "donki/cme:endDate=2024-01-07&startDate=2024-01-01");
+    }
+
+    #[test]
+    fn resolves_paths_preferring_static_routes() {
+        assert_eq!(resolve("/api/neo/browse").unwrap().route, "/api/neo/browse");
+        assert_eq!(resolve("/api/neo/3542519").unwrap().route, "/api/neo/:asteroid_id");
+        assert_eq!(resolve("/api/mars-photos/curiosity/photos/").unwrap().route, "/api/mars-photos/:rover/photos");
+        assert!(resolve("/api/neo").is_none());
+        assert!(resolve("/api/usage").is_none());
+    }
+
+    #[test]
+    fn extracts_path_params() {
+        let endpoint = find("/api/mars-photos/:rover/photos").unwrap();
+
+        assert_eq!(
+            endpoint.path_params("/api/mars-photos/curiosity/photos"),
+            Some(HashMap::from([("rover".to_string(), "curiosity".to_string())]))
+        );
+        assert_eq!(endpoint.path_params("/api/mars-photos/curiosity/latest"), None);
+    }
+
+    #[test]
+    fn drops_client_key_from_forwarded_params() {
+        let (endpoint, req) = request("/api/donki/cme", &[], &[("key", "secret"), ("startDate", "2024-01-01")]);
+
+        assert_eq!(endpoint.cache_key(&req), "donki/cme:startDate=2024-01-01");
+    }
+
+    #[test]
+    fn applies_defaults_and_query_allowlist() {
+        let (endpoint, req) = request("/api/exoplanets/query", &[], &[("query", "select 1"), ("utm_source", "x")]);
+
+        assert_eq!(
+            endpoint.upstream_query(&req),
+            vec![("format".to_string(), "json".to_string()), ("query".to_string(), "select 1".to_string())]
+        );
+    }
+
+    #[test]
+    fn canonicalizes_values_by_kind() {
+        assert_eq!(date("d").canonicalize(" 2024-1-5 "), "2024-01-05");
+        assert_eq!(date("d").canonicalize("now"), "now");
+        assert_eq!(integer("n").canonicalize("+007"), "7");
+        assert_eq!(number("n").canonicalize("29.780"), "29.78");
+        assert_eq!(number("n").canonicalize("-0.0"), "0");
+        assert_eq!(number("n").canonicalize("NaN"), "NaN");
+        assert_eq!(flag("f").canonicalize("YES"), "true");
+        assert_eq!(flag("f").canonicalize("0"), "false");
+        assert_eq!(lowercase("l").canonicalize("NAVCAM"), "navcam");
+        assert_eq!(text("t").canonicalize("Apollo 11"), "Apollo 11");
+    }
+
+    #[test]
+    fn equivalent_requests_share_a_cache_key() {
+        let (endpoint, canonical) = request("/api/mars-photos/:rover/photos", &[("rover", "curiosity")], &[("sol", "1000"), ("camera", "navcam")]);
+        let (_, variant) = request(
+            "/api/mars-photos/:rover/photos",
+            &[("rover", "Curiosity")],
+            &[("camera", "NAVCAM"), ("utm_source", "newsletter"), ("api_key", "DEMO_KEY"), ("sol", "01000")],
+        );
+
+        assert_eq!(endpoint.cache_key(&variant), endpoint.cache_key(&canonical));
+        assert_eq!(endpoint.cache_key(&canonical), "mars-photos/curiosity/photos:camera=navcam&sol=1000");
+        assert!(endpoint.check(&canonical, None).is_ok());
+        assert_eq!(variant.unknown, ["utm_source", "api_key"]);
+    }
+
+    #[test]
+    fn fills_in_defaults_before_keying() {
+        let (endpoint, explicit) = request("/api/neo/browse", &[], &[("page", "0"), ("size", "20")]);
+        let (_, implicit) = request("/api/neo/browse", &[], &[]);
+
+        assert_eq!(endpoint.cache_key(&implicit), endpoint.cache_key(&explicit));
+    }
+
+    fn problems(route: &str, path: &[(&str, &str)], query: &[(&str, &str)]) -> Vec<String> {
+        let (endpoint, req) = request(route, path, query);
+        match endpoint.check(&req, None) {
+            Ok(()) => Vec::new(),
+            Err(NasaApiError::Validation(problems)) => problems.iter().map(Problem::to_string).collect(),
+            Err(e) => panic!("unexpected error {e}"),
+        }
+    }
+
+    #[test]
+    fn reports_missing_required_params() {
+        assert_eq!(problems("/api/ssd/sbdb", &[], &[]), ["sstr is required"]);
+    }
+
+    #[test]
+    fn reports_every_problem_at_once() {
+        assert_eq!(
+            problems("/api/neo/browse", &[], &[("page", "first"), ("size", "50"), ("utm_source", "x"), ("key", "client")]),
+            [
+                "utm_source is not a parameter of this endpoint",
+                "page must be a whole number",
+                "size must be between 1 and 20",
+            ]
+        );
+        assert_eq!(
+            problems("/api/epic/natural/date/:date", &[("date", "yesterday")], &[("q", "x")]),
+            ["q is not a parameter of this endpoint", "date must be a date in YYYY-MM-DD format"]
+        );
+    }
+
+    #[test]
+    fn checks_allowed_values() {
+        assert_eq!(
+            problems("/api/donki/notifications", &[], &[("type", "flare")]),
+            ["type must be one of: all, FLR, SEP, CME, IPS, MPC, GST, RBE, report"]
+        );
+        assert!(problems("/api/donki/notifications", &[], &[("type", "FLR")]).is_empty());
+        assert_eq!(
+            problems("/api/mars-photos/manifests/:rover", &[("rover", "zhurong")], &[]),
+            ["rover must be one of: curiosity, opportunity, spirit"]
+        );
+    }
+
+    #[test]
+    fn checks_apod_parameter_combinations() {
+        assert_eq!(
+            problems("/api/apod", &[], &[("count", "5"), ("date", "2024-01-01")]),
+            ["count cannot be combined with date"]
+        );
+        assert_eq!(
+            problems("/api/apod", &[], &[("end_date", "2024-01-01")]),
+            ["start_date is required with end_date"]
+        );
+        assert_eq!(
+            problems("/api/apod", &[], &[("count", "500")]),
+            ["count must be between 1 and 100"]
+        );
+        assert!(problems("/api/apod", &[], &[("start_date", "2024-01-01"), ("end_date", "2024-01-05")]).is_empty());
+    }
+
+    #[test]
+    fn checks_date_ranges() {
+        assert_eq!(
+            problems("/api/neo/feed", &[], &[("start_date", "2024-01-09"), ("end_date", "2024-01-01")]),
+            ["end_date must not be before start_date"]
+        );
+        assert!(problems("/api/neo/feed", &[], &[("start_date", "2024-01-01"), ("end_date", "2024-01-31")]).is_empty());
+        assert_eq!(
+            problems("/api/neo/feed", &[], &[("start_date", "2024-01-01"), ("end_date", "2024-03-01"), ("size", "huge")]),
+            ["size must be one of: small, medium, large", "end_date must be at most 31 days after start_date"]
+        );
+        assert_eq!(
+            problems("/api/donki/gst", &[], &[("startDate", "2024-02-01"), ("endDate", "2024-01-01")]),
+            ["endDate must not be before startDate"]
+        );
+    }
+
+    #[test]
+    fn checks_cameras_per_rover() {
+        assert_eq!(
+            problems("/api/mars-photos/:rover/photos", &[("rover", "spirit")], &[("sol", "1"), ("camera", "mast")]),
+            ["camera must be one of the spirit cameras: fhaz, rhaz, navcam, pancam, minites"]
+        );
+        assert!(problems("/api/mars-photos/:rover/photos", &[("rover", "curiosity")], &[("sol", "1"), ("camera", "MAST")]).is_empty());
+    }
+
+    #[test]
+    fn split_limits_are_clamped_to_the_ceiling() {
+        let split = find("/api/neo/feed").unwrap().split.unwrap();
+        let (_, feed) = request("/api/neo/feed", &[], &[("start_date", "2024-01-01"), ("end_date", "2024-01-20")]);
+
+        assert_eq!(split.limit(None), (31, None));
+        assert_eq!(split.limit(Some("14")), (14, None));
+        assert_eq!(split.limit(Some("365")).0, neo_feed::MAX_DAYS_CEILING);
+        assert!(split.limit(Some("365")).1.unwrap().starts_with("Clamping NEO_FEED_MAX_DAYS"));
+        assert_eq!(split.limit(Some("-3")).0, 31);
+        assert!(find("/api/neo/feed").unwrap().check(&feed, Some(14)).is_err());
+    }
+
+    #[test]
+    fn normalize_is_an_option_outside_the_cache_key() {
+        let (endpoint, plain) = request("/api/donki/flr", &[], &[("startDate", "2024-01-01")]);
+        let (_, normalized) = request("/api/donki/flr", &[], &[("startDate", "2024-01-01"), ("normalize", "YES"), ("key", "client")]);
+
+        assert_eq!(endpoint.cache_key(&normalized), endpoint.cache_key(&plain));
+        assert_eq!(normalized.options, [("normalize".to_string(), "true".to_string())]);
+        assert!(normalized.normalized() && !plain.normalized());
+        assert!(endpoint.check(&normalized, None).is_ok());
+    }
+
+    #[test]
+    fn checks_normalize() {
+        assert_eq!(problems("/api/ssd/fireballs", &[], &[("normalize", "maybe")]), ["normalize must be true or false"]);
+        assert_eq!(problems("/api/techtransfer/patents", &[], &[("normalize", "true")]), ["normalize is not supported by this endpoint"]);
+        assert!(problems("/api/techtransfer/patents", &[], &[("normalize", "false")]).is_empty());
+    }
+
+    #[test]
+    fn neo_feed_refinements_stay_out_of_the_cache_key() {
+        let (endpoint, plain) = request("/api/neo/feed", &[], &[("start_date", "2024-01-01")]);
+        let (_, refined) = request("/api/neo/feed", &[], &[("start_date", "2024-01-01"), ("sort", "-Velocity"), ("flatten", "1")]);
+
+        assert_eq!(endpoint.cache_key(&refined), endpoint.cache_key(&plain));
+        assert_eq!(refined.option("sort"), Some("-velocity"));
+        assert!(refined.unknown.is_empty());
+        assert_eq!(
+            problems("/api/neo/feed", &[], &[("size", "huge"), ("min_distance", "0.2"), ("max_distance", "0.1")]),
+            ["size must be one of: small, medium, large", "max_distance must not be less than min_distance"]
+        );
+        assert_eq!(problems("/api/neo/browse", &[], &[("sort", "velocity")]), ["sort is not a parameter of this endpoint"]);
+    }
+
+    #[test]
+    fn checks_fields() {
+        let (endpoint, req) = request("/api/mars-photos/:rover/latest", &[("rover", "curiosity")], &[("fields", "photos[].img_src")]);
+        assert!(endpoint.check(&req, None).is_ok());
+        assert_eq!(endpoint.cache_key(&req), "mars-photos/curiosity/latest:");
+
+        assert_eq!(problems("/api/apod", &[], &[("fields", "title,,url..hd")]), ["fields has an empty segment in url..hd"]);
+        assert_eq!(problems("/api/earth/imagery", &[], &[("lat", "1"), ("lon", "2"), ("fields", "x")]), ["fields is not supported by this endpoint"]);
+        assert!(problems("/api/apod", &[], &[("fields", "")]).is_empty());
+    }
+
+    #[test]
+    fn normalized_endpoints_are_served_on_v2() {
+        let lookup = find("/api/neo/:asteroid_id").unwrap();
+        assert_eq!(lookup.versions(), [Version::V1, Version::V2]);
+        assert_eq!(lookup.route_on(Version::V2), "/api/v2/neo/:asteroid_id");
+        assert_eq!(find("/api/techtransfer/patents").unwrap().versions(), [Version::V1]);
+        assert_eq!(Version::unversioned("/api/v2/neo/3542519"), "/api/neo/3542519");
+        assert_eq!(Version::unversioned("/api/neo/3542519"), "/api/neo/3542519");
+
+        let (browse, plain) = request("/api/neo/browse", &[], &[]);
+        let (_, normalized) = request("/api/neo/browse", &[], &[("normalize", "true")]);
+        let page = serde_json::json!({
+            "links": {"self": "https://api.nasa.gov/neo/rest/v1/neo/browse"},
+            "page": {"size": 20, "total_elements": 1, "total_pages": 1, "number": 0},
+            "near_earth_objects": []
+        });
+        assert!(browse.normalizer(Version::V1, &plain).is_none());
+        assert!((browse.normalizer(Version::V1, &normalized).unwrap().apply)(page.clone()).unwrap().get("links").is_some());
+        assert!((browse.normalizer(Version::V2, &plain).unwrap().apply)(page).unwrap().get("items").is_some());
+    }
+
+    #[test]
+    fn runs_endpoint_validation() {
+        let (endpoint, req) = request("/api/mars-photos/:rover/photos", &[("rover", "zhurong")], &[("sol", "1")]);
+        assert!(endpoint.check(&req, None).is_err());
+
+        let (endpoint, req) = request("/api/mars-photos/:rover/photos", &[("rover", "curiosity")], &[("sol", "1"), ("earth_date", "2020-01-01")]);
+        assert!(endpoint.check(&req, None).is_err());
+
+        let (endpoint, req) = request("/api/earth/assets", &[], &[("lat", "95"), ("lon", "0"), ("date", "2020-01-01")]);
+        assert!(endpoint.check(&req, None).is_err());
+
+        let (endpoint, req) = request("/api/earth/assets", &[], &[("lat", "29.78"), ("lon", "-95.33"), ("date", "2020-01-01")]);
+        assert!(endpoint.check(&req, None).is_ok());
+    }
+}
@@ -1,108 +1,321 @@
+//! The worker's route table.
+//!
+//! [`routes`] is the single list of every route the worker serves: its own pages
+//! and admin API, followed by the proxied endpoints from [`crate::registry`] on
+//! each [`Version`] they support. The router built from it by [`create_router`]
+//! and the pure lookup in [`resolve`] both give static segments precedence over
+//! parameters, so `/api/neo/browse` never reaches the asteroid lookup at
+//! `/api/neo/:asteroid_id`.
+//!
+//! Routes being retired are listed in [`DEPRECATIONS`]; their responses carry
+//! `Deprecation`, `Sunset` and `Link` headers until they are removed.
+
+use std::sync::OnceLock;
+
+use chrono::NaiveDate;
 use worker::*;
-use crate::{handlers, middleware};
-
-#[allow(dead_code)]
-pub type HandlerContext = (Env, Context);
-
-#[allow(dead_code)]
-pub fn create_router(env: Env, ctx: Context) -> Router<'static, HandlerContext> {
-    Router::with_data((env, ctx))
-        // Landing page - serve the HTML
-        .get_async("/", |_, _| async { 
-            let html = include_str!("../docs/index.html");
-            Response::from_html(html)
-        })
-        
-        // Serve CSS
-        .get_async("/styles.css", |_, _| async {
-            let css = include_str!("../docs/styles.css");
-            Response::ok(css)
-                .map(|mut res| {
-                    res.headers_mut()
-                        .set("Content-Type", "text/css")
-                        .unwrap();
-                    res
-                })
-        })
-        
-        // Serve favicon
-        .get_async("/favicon.svg", |_, _| async {
-            let svg = include_str!("../docs/favicon.svg");
-            Response::ok(svg)
-                .map(|mut res| {
-                    res.headers_mut()
-                        .set("Content-Type", "image/svg+xml")
-                        .unwrap();
-                    res
-                })
-        })
-        
-        // Health check
-        .get_async("/health", |_, _| async { 
-            Response::ok("OK")
-        })
-        
-        // APOD (Astronomy Picture of the Day)
-        .get_async("/api/apod", handlers::apod::get_apod)
-        
-        // NeoWs (Near Earth Objects)
-        .get_async("/api/neo/feed", handlers::neo::get_neo_feed)
-        .get_async("/api/neo/:asteroid_id", handlers::neo::get_neo_lookup)
-        .get_async("/api/neo/browse", handlers::neo::get_neo_browse)
-        
-        // DONKI (Space Weather)
-        .get_async("/api/donki/cme", handlers::donki::get_cme)
-        .get_async("/api/donki/cme-analysis", handlers::donki::get_cme_analysis)
-        .get_async("/api/donki/gst", handlers::donki::get_gst)
-        .get_async("/api/donki/ips", handlers::donki::get_ips)
-        .get_async("/api/donki/flr", handlers::donki::get_flr)
-        .get_async("/api/donki/sep", handlers::donki::get_sep)
-        .get_async("/api/donki/mpc", handlers::donki::get_mpc)
-        .get_async("/api/donki/rbe", handlers::donki::get_rbe)
-        .get_async("/api/donki/hss", handlers::donki::get_hss)
-        .get_async("/api/donki/wsa-enlil", handlers::donki::get_wsa_enlil)
-        .get_async("/api/donki/notifications", handlers::donki::get_notifications)
-        
-        // Earth Imagery
-        .get_async("/api/earth/imagery", handlers::earth::get_imagery)
-        .get_async("/api/earth/assets", handlers::earth::get_assets)
-        
-        // EPIC
-        .get_async("/api/epic/natural/all", handlers::epic::get_natural_all)
-        .get_async("/api/epic/natural/date/:date", handlers::epic::get_natural_date)
-        .get_async("/api/epic/enhanced/all", handlers::epic::get_enhanced_all)
-        .get_async("/api/epic/enhanced/date/:date", handlers::epic::get_enhanced_date)
-        
-        // Mars Rover Photos
-        .get_async("/api/mars-photos/:rover/photos", handlers::mars::get_rover_photos)
-        .get_async("/api/mars-photos/:rover/latest", handlers::mars::get_latest_photos)
-        .get_async("/api/mars-photos/manifests/:rover", handlers::mars::get_manifest)
-        
-        // Tech Transfer
-        .get_async("/api/techtransfer/patents", handlers::tech::get_patents)
-        .get_async("/api/techtransfer/patents-issued", handlers::tech::get_patents_issued)
-        .get_async("/api/techtransfer/software", handlers::tech::get_software)
-        .get_async("/api/techtransfer/spinoffs", handlers::tech::get_spinoffs)
-        
-        // NASA Image and Video Library
-        .get_async("/api/media/search", handlers::media::search_media)
-        .get_async("/api/media/asset/:nasa_id", handlers::media::get_asset)
-        .get_async("/api/media/metadata/:nasa_id", handlers::media::get_metadata)
-        .get_async("/api/media/captions/:nasa_id", handlers::media::get_captions)
-        
-        // Exoplanet Archive
-        .get_async("/api/exoplanets/query", handlers::exoplanets::query_exoplanets)
-        
-        // SSD/CNEOS (Solar System Dynamics)
-        .get_async("/api/ssd/cad", handlers::ssd::get_close_approach)
-        .get_async("/api/ssd/sbdb", handlers::ssd::get_small_body)
-        .get_async("/api/ssd/sentry", handlers::ssd::get_sentry)
-        .get_async("/api/ssd/scout", handlers::ssd::get_scout)
-        .get_async("/api/ssd/nhats", handlers::ssd::get_nhats)
-        .get_async("/api/ssd/fireballs", handlers::ssd::get_fireballs)
-        
-        // Apply CORS middleware to all routes
-        .or_else_any_method_async("/*catchall", |req, _| async move {
-            middleware::cors::handle_cors(req).await
-        })
-}
\ No newline at end of file
+
+use crate::cache::http;
+use crate::handlers::{self, HandlerContext};
+use crate::registry::{self, Endpoint, Version};
+use crate::middleware;
+
+/// Retirement schedule of a route.
+#[derive(Debug)]
+pub struct Deprecation {
+    /// Pattern of the retired route, as in the route table.
+    pub pattern: &'static str,
+    /// Day the route was deprecated, sent as `Deprecation` (RFC 9745).
+    pub deprecated: NaiveDate,
+    /// Day after which the route may stop answering, sent as `Sunset` (RFC 8594).
+    pub sunset: NaiveDate,
+    /// Route that replaces it, linked with `rel="successor-version"`.
+    pub successor: Option<&'static str>,
+}
+
+impl Deprecation {
+    /// Headers announcing the retirement.
+    pub fn headers(&self) -> Vec<(&'static str, String)> {
+        let midnight = |date: NaiveDate| date.and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let mut headers = vec![
+            ("Deprecation", format!("@{}", midnight(self.deprecated).timestamp())),
+            ("Sunset", http::http_date(midnight(self.sunset))),
+        ];
+        if let Some(successor) = self.successor {
+            headers.push(("Link", format!("<{successor}>; rel=\"successor-version\"")));
+        }
+        headers
+    }
+}
+
+/// Routes on their way out, e.g.
+/// `Deprecation { pattern: "/api/apod", deprecated: .., sunset: .., successor: Some("/api/v2/apod") }`.
+pub static DEPRECATIONS: &[Deprecation] = &[];
+
+/// What serves a route.
+#[derive(Debug, Clone, Copy)]
+pub enum Target {
+    Landing,
+    Health,
+    SwaggerUi,
+    OpenApiJson,
+    OpenApiYaml,
+    Usage,
+    AdminUsage,
+    AdminCacheList,
+    AdminCachePurge,
+    AdminCacheStats,
+    Proxy(&'static Endpoint, Version),
+}
+
+/// One entry of the route table.
+#[derive(Debug, Clone)]
+pub struct Route {
+    pub method: Method,
+    /// Pattern in router syntax, e.g. `/api/neo/:asteroid_id`.
+    pub pattern: String,
+    pub target: Target,
+    pub deprecation: Option<&'static Deprecation>,
+}
+
+impl Route {
+    fn new(method: Method, pattern: impl Into<String>, target: Target) -> Self {
+        let pattern = pattern.into();
+        let deprecation = DEPRECATIONS.iter().find(|d| d.pattern == pattern);
+        Self { method, pattern, target, deprecation }
+    }
+
+    fn get(pattern: impl Into<String>, target: Target) -> Self {
+        Self::new(Method::Get, pattern, target)
+    }
+
+    /// Number of `:param` segments; routes with fewer win when several match.
+    fn params(&self) -> usize {
+        self.pattern.split('/').filter(|segment| segment.starts_with(':')).count()
+    }
+
+    fn matches(&self, path: &str) -> bool {
+        registry::route_params(&self.pattern, path).is_some()
+    }
+}
+
+/// Every route the worker serves, static routes before parameterised ones.
+/// Built once per isolate.
+pub fn routes() -> &'static [Route] {
+    static ROUTES: OnceLock<Vec<Route>> = OnceLock::new();
+    ROUTES.get_or_init(build_routes)
+}
+
+fn build_routes() -> Vec<Route> {
+    let mut routes = vec![
+        Route::get("/", Target::Landing),
+        Route::get("/health", Target::Health),
+        // API documentation
+        Route::get("/api/docs", Target::SwaggerUi),
+        Route::get("/api/docs/", Target::SwaggerUi),
+        Route::get("/api/docs/openapi.json", Target::OpenApiJson),
+        Route::get("/api/docs/openapi.yaml", Target::OpenApiYaml),
+        // Usage reports
+        Route::get("/api/usage", Target::Usage),
+        Route::get("/api/admin/usage", Target::AdminUsage),
+        // Cache administration
+        Route::get("/api/admin/cache", Target::AdminCacheList),
+        Route::new(Method::Delete, "/api/admin/cache", Target::AdminCachePurge),
+        Route::get("/api/admin/cache/stats", Target::AdminCacheStats),
+    ];
+    // Proxied NASA endpoints, declared in the registry
+    for endpoint in registry::ENDPOINTS {
+        for &version in endpoint.versions() {
+            routes.push(Route::get(endpoint.route_on(version), Target::Proxy(endpoint, version)));
+        }
+    }
+
+    routes.sort_by_key(Route::params);
+    routes
+}
+
+/// The route a request reaches, with the same precedence as the router. Unlike
+/// the router, a trailing slash is ignored, so the route is also found for
+/// requests that 404.
+pub fn resolve(method: &Method, path: &str) -> Option<&'static Route> {
+    routes()
+        .iter()
+        .filter(|route| &route.method == method && route.matches(path))
+        .min_by_key(|route| route.params())
+}
+
+/// Builds the worker router from [`routes`]. Unmatched paths fall through to
+/// the CORS handler, which answers preflights and 404s everything else.
+pub fn create_router(data: HandlerContext) -> Router<'static, HandlerContext> {
+    let mut router = Router::with_data(data);
+    for route in routes() {
+        let (target, deprecation) = (route.target, route.deprecation);
+        let handler = move |req, ctx| dispatch(target, deprecation, req, ctx);
+        router = match route.method {
+            Method::Delete => router.delete_async(&route.pattern, handler),
+            _ => router.get_async(&route.pattern, handler),
+        };
+    }
+
+    router.or_else_any_method_async("/*catchall", |req, _| async move {
+        middleware::cors::handle_cors(req).await
+    })
+}
+
+/// Runs the handler behind `target`, turning [`crate::NasaApiError`]s into JSON
+/// responses and announcing the route's `deprecation`, if any.
+async fn dispatch(
+    target: Target,
+    deprecation: Option<&'static Deprecation>,
+    req: Request,
+    ctx: RouteContext<HandlerContext>,
+) -> Result<Response> {
+    let mut response = handle(target, req, ctx).await?;
+    for (name, value) in deprecation.map(Deprecation::headers).unwrap_or_default() {
+        response.headers_mut().set(name, &value)?;
+    }
+    Ok(response)
+}
+
+async fn handle(target: Target, req: Request, ctx: RouteContext<HandlerContext>) -> Result<Response> {
+    let result = match target {
+        Target::Landing => return handlers::pages::landing(),
+        Target::Health => return Response::ok("OK"),
+        Target::SwaggerUi => return handlers::docs::get_swagger_ui(req, ctx).await,
+        Target::OpenApiJson => return handlers::docs::get_openapi_json(req, ctx).await,
+        Target::OpenApiYaml => return handlers::docs::get_openapi_yaml(req, ctx).await,
+        Target::Usage => handlers::usage::get_usage(req, ctx).await,
+        Target::AdminUsage => handlers::usage::get_all_usage(req, ctx).await,
+        Target::AdminCacheList => handlers::cache::list_keys(req, ctx).await,
+        Target::AdminCachePurge => handlers::cache::purge(req, ctx).await,
+        Target::AdminCacheStats => handlers::cache::stats(req, ctx).await,
+        Target::Proxy(endpoint, version) => handlers::proxy::serve(endpoint, version, req, ctx).await,
+    };
+    Ok(result.unwrap_or_else(|e| e.to_response()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Concrete request paths for every path documented in `openapi.yaml`, with
+    /// `{param}` placeholders filled from the parameter's enum or format.
+    fn documented_paths() -> Vec<(String, String)> {
+        let spec: serde_yaml::Value = serde_yaml::from_str(include_str!("../openapi.yaml")).unwrap();
+        let mut paths = Vec::new();
+
+        for (path, item) in spec["paths"].as_mapping().unwrap() {
+            let template = path.as_str().unwrap().to_string();
+            let mut concrete = template.clone();
+            for param in item["get"]["parameters"].as_sequence().into_iter().flatten() {
+                if param["in"].as_str() != Some("path") {
+                    continue;
+                }
+                let name = param["name"].as_str().unwrap();
+                let schema = &param["schema"];
+                let sample = match (schema["enum"].get(0).and_then(|v| v.as_str()), schema["format"].as_str()) {
+                    (Some(first), _) => first.to_string(),
+                    (None, Some("date")) => "2024-01-01".to_string(),
+                    _ => "sample-id".to_string(),
+                };
+                concrete = concrete.replace(&format!("{{{name}}}"), &sample);
+            }
+            assert!(!concrete.contains('{'), "{template} has an undocumented path parameter");
+            paths.push((template, concrete));
+        }
+        paths
+    }
+
+    fn pattern_for(template: &str) -> String {
+        template
+            .split('/')
+            .map(|segment| match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                Some(name) => format!(":{name}"),
+                None => segment.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    #[test]
+    fn every_documented_path_reaches_its_endpoint() {
+        for (template, path) in documented_paths() {
+            let route = resolve(&Method::Get, &path).unwrap_or_else(|| panic!("{path} is not routed"));
+
+            assert_eq!(route.pattern, pattern_for(&template), "{path} reached the wrong route");
+            assert!(matches!(route.target, Target::Proxy(endpoint, version) if endpoint.route_on(version) == route.pattern));
+        }
+    }
+
+    #[test]
+    fn router_agrees_with_the_route_table() {
+        // The worker router matches with matchit, one tree per method
+        let mut router = matchit::Router::new();
+        for route in routes().iter().filter(|route| route.method == Method::Get) {
+            router.insert(route.pattern.clone(), route.pattern.clone()).unwrap();
+        }
+
+        for (_, path) in documented_paths() {
+            let expected = &resolve(&Method::Get, &path).unwrap().pattern;
+            assert_eq!(router.at(&path).unwrap().value, expected, "{path}");
+        }
+    }
+
+    #[test]
+    fn static_segments_shadow_parameters() {
+        assert_eq!(resolve(&Method::Get, "/api/neo/browse").unwrap().pattern, "/api/neo/browse");
+        assert_eq!(resolve(&Method::Get, "/api/neo/3542519").unwrap().pattern, "/api/neo/:asteroid_id");
+        assert_eq!(
+            resolve(&Method::Get, "/api/mars-photos/manifests/curiosity").unwrap().pattern,
+            "/api/mars-photos/manifests/:rover"
+        );
+        assert!(matches!(resolve(&Method::Delete, "/api/admin/cache").unwrap().target, Target::AdminCachePurge));
+        assert!(resolve(&Method::Post, "/api/apod").is_none());
+    }
+
+    #[test]
+    fn normalized_endpoints_are_also_served_on_v2() {
+        let route = resolve(&Method::Get, "/api/v2/neo/3542519").unwrap();
+        assert_eq!(route.pattern, "/api/v2/neo/:asteroid_id");
+        assert!(matches!(route.target, Target::Proxy(endpoint, Version::V2) if endpoint.route == "/api/neo/:asteroid_id"));
+
+        assert!(resolve(&Method::Get, "/api/v2/neo/browse").is_some());
+        assert!(resolve(&Method::Get, "/api/v2/techtransfer/patents").is_none());
+        assert!(resolve(&Method::Get, "/api/v2/docs").is_none());
+    }
+
+    #[test]
+    fn deprecations_announce_sunset_and_successor() {
+        let deprecation = Deprecation {
+            pattern: "/api/apod",
+            deprecated: NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+            sunset: NaiveDate::from_ymd_opt(2026, 7, 1).unwrap(),
+            successor: Some("/api/v2/apod"),
+        };
+
+        assert_eq!(
+            deprecation.headers(),
+            [
+                ("Deprecation", "@1767225600".to_string()),
+                ("Sunset", "Wed, 01 Jul 2026 00:00:00 GMT".to_string()),
+                ("Link", "</api/v2/apod>; rel=\"successor-version\"".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn deprecations_name_routes_in_the_table() {
+        let patterns: Vec<&str> = routes().iter().map(|route| route.pattern.as_str()).collect();
+        for deprecation in DEPRECATIONS {
+            assert!(patterns.contains(&deprecation.pattern), "{} is not a route", deprecation.pattern);
+            assert!(deprecation.deprecated <= deprecation.sunset, "{} sunsets before it is deprecated", deprecation.pattern);
+        }
+    }
+
+    #[test]
+    fn statics_are_listed_before_params() {
+        let params: Vec<usize> = routes().iter().map(Route::params).collect();
+
+        assert!(params.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+}
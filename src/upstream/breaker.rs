@@ -0,0 +1,205 @@
+//! Circuit breaker per upstream host, persisted in the `RATE_LIMIT` KV namespace.
+//!
+//! After [`BreakerConfig::failure_threshold`] consecutive failed requests to a host
+//! the circuit opens and requests fail fast for [`BreakerConfig::cooldown`]. The
+//! first request after the cooldown is let through as a probe (half-open): if it
+//! succeeds the circuit closes, otherwise it opens again. While a probe is in
+//! flight other requests keep failing fast. KV is eventually consistent, so
+//! isolates may briefly disagree about the state; the breaker only needs to be
+//! roughly right to keep traffic off a dead upstream.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use worker::{console_error, kv::KvStore, Env};
+
+/// Breaker state stored per host.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum BreakerState {
+    Closed { failures: u32 },
+    Open { since: DateTime<Utc> },
+    HalfOpen { since: DateTime<Utc> },
+}
+
+impl Default for BreakerState {
+    fn default() -> Self {
+        BreakerState::Closed { failures: 0 }
+    }
+}
+
+/// Whether a request may go ahead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Admission {
+    Allow,
+    /// Allowed as the single half-open probe; the caller records the half-open state.
+    Probe,
+    /// Failing fast until the given time.
+    Reject { until: DateTime<Utc> },
+}
+
+/// Thresholds shared by every breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreakerConfig {
+    pub failure_threshold: u32,
+    pub cooldown: Duration,
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cooldown: Duration::seconds(30),
+        }
+    }
+}
+
+impl BreakerConfig {
+    pub fn admit(&self, state: &BreakerState, now: DateTime<Utc>) -> Admission {
+        match *state {
+            BreakerState::Closed { .. } => Admission::Allow,
+            BreakerState::Open { since } | BreakerState::HalfOpen { since } => {
+                // A half-open probe that never reported back is retried after another cooldown
+                let until = since + self.cooldown;
+                if now >= until {
+                    Admission::Probe
+                } else {
+                    Admission::Reject { until }
+                }
+            }
+        }
+    }
+
+    pub fn on_success(&self) -> BreakerState {
+        BreakerState::default()
+    }
+
+    pub fn on_failure(&self, state: &BreakerState, now: DateTime<Utc>) -> BreakerState {
+        match *state {
+            BreakerState::Closed { failures } if failures + 1 < self.failure_threshold => {
+                BreakerState::Closed { failures: failures + 1 }
+            }
+            _ => BreakerState::Open { since: now },
+        }
+    }
+}
+
+/// Where breaker state lives between requests.
+#[async_trait(?Send)]
+pub trait BreakerStore {
+    async fn load(&self, host: &str) -> BreakerState;
+    async fn save(&self, host: &str, state: &BreakerState);
+}
+
+/// Breaker state kept only for the lifetime of one client; effectively disables
+/// sharing between requests. Used where no KV namespace is available.
+#[derive(Default)]
+pub struct MemoryBreakerStore {
+    states: RefCell<HashMap<String, BreakerState>>,
+}
+
+#[async_trait(?Send)]
+impl BreakerStore for MemoryBreakerStore {
+    async fn load(&self, host: &str) -> BreakerState {
+        self.states.borrow().get(host).cloned().unwrap_or_default()
+    }
+
+    async fn save(&self, host: &str, state: &BreakerState) {
+        self.states.borrow_mut().insert(host.to_string(), state.clone());
+    }
+}
+
+/// Breaker state shared by all isolates through KV.
+pub struct KvBreakerStore {
+    kv: KvStore,
+}
+
+impl KvBreakerStore {
+    /// Entries outlive any sensible cooldown but don't linger forever.
+    const TTL_SECONDS: u64 = 3600;
+
+    pub fn new(env: &Env) -> Option<Self> {
+        env.kv("RATE_LIMIT").ok().map(|kv| Self { kv })
+    }
+
+    fn key(host: &str) -> String {
+        format!("circuit:{host}")
+    }
+}
+
+#[async_trait(?Send)]
+impl BreakerStore for KvBreakerStore {
+    async fn load(&self, host: &str) -> BreakerState {
+        match self.kv.get(&Self::key(host)).json::<BreakerState>().await {
+            Ok(state) => state.unwrap_or_default(),
+            Err(e) => {
+                console_error!("Failed to read circuit state for {}: {}", host, e);
+                BreakerState::default()
+            }
+        }
+    }
+
+    async fn save(&self, host: &str, state: &BreakerState) {
+        let result = match serde_json::to_string(state) {
+            Ok(value) => match self.kv.put(&Self::key(host), value) {
+                Ok(put) => put.expiration_ttl(Self::TTL_SECONDS).execute().await,
+                Err(e) => Err(e),
+            },
+            Err(e) => {
+                console_error!("Failed to serialize circuit state: {}", e);
+                return;
+            }
+        };
+
+        if let Err(e) = result {
+            console_error!("Failed to write circuit state for {}: {}", host, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: BreakerConfig = BreakerConfig { failure_threshold: 3, cooldown: Duration::seconds(30) };
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 + seconds, 0).unwrap()
+    }
+
+    #[test]
+    fn opens_after_consecutive_failures() {
+        let mut state = BreakerState::default();
+        for _ in 0..2 {
+            state = CONFIG.on_failure(&state, at(0));
+            assert_eq!(CONFIG.admit(&state, at(0)), Admission::Allow);
+        }
+
+        state = CONFIG.on_failure(&state, at(0));
+
+        assert_eq!(state, BreakerState::Open { since: at(0) });
+        assert_eq!(CONFIG.admit(&state, at(10)), Admission::Reject { until: at(30) });
+    }
+
+    #[test]
+    fn probes_after_cooldown_and_reopens_on_failure() {
+        let open = BreakerState::Open { since: at(0) };
+        assert_eq!(CONFIG.admit(&open, at(30)), Admission::Probe);
+
+        let probing = BreakerState::HalfOpen { since: at(30) };
+        assert_eq!(CONFIG.admit(&probing, at(31)), Admission::Reject { until: at(60) });
+        assert_eq!(CONFIG.on_failure(&probing, at(32)), BreakerState::Open { since: at(32) });
+        assert_eq!(CONFIG.on_success(), BreakerState::default());
+    }
+
+    #[test]
+    fn state_round_trips_through_json() {
+        let state = BreakerState::HalfOpen { since: at(0) };
+        let json = serde_json::to_string(&state).unwrap();
+
+        assert!(json.contains(r#""state":"half_open""#));
+        assert_eq!(serde_json::from_str::<BreakerState>(&json).unwrap(), state);
+    }
+}
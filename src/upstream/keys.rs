@@ -0,0 +1,218 @@
+//! Pool of NASA API keys with per-key quota tracking, persisted in the
+//! `RATE_LIMIT` KV namespace.
+//!
+//! api.nasa.gov gives each key a fixed number of requests per rolling hour and
+//! reports what is left in `X-RateLimit-Remaining`. The client records that
+//! figure per key and sends each request with the key that has the most quota
+//! left. A key answering 429 is set aside for [`EXHAUSTION_COOLDOWN`] and the
+//! request is retried with the next one. Like circuit breaker state, the figures
+//! are shared through eventually consistent KV and only need to be roughly right;
+//! to keep KV writes down, a figure is only written when it crosses a multiple of
+//! [`SAVE_GRANULARITY`].
+
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use worker::{console_error, kv::KvStore, Env};
+
+/// How long a key that answered 429 is skipped.
+pub const EXHAUSTION_COOLDOWN: Duration = Duration::hours(1);
+
+/// Remaining-quota figures are persisted in steps of this size.
+pub const SAVE_GRANULARITY: u32 = 50;
+
+/// Splits configured key values, which may hold several comma-separated keys,
+/// into a pool without blanks or duplicates, keeping the configured order.
+pub fn parse_pool(values: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut pool: Vec<String> = Vec::new();
+    for value in values {
+        for key in value.split(',').map(str::trim).filter(|key| !key.is_empty()) {
+            if !pool.iter().any(|k| k == key) {
+                pool.push(key.to_string());
+            }
+        }
+    }
+    pool
+}
+
+/// What is known about one key's quota.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyState {
+    /// Requests left in the current window, as last reported by the upstream.
+    #[serde(default)]
+    pub remaining: Option<u32>,
+    /// Set after a 429; the key is skipped until then.
+    #[serde(default)]
+    pub exhausted_until: Option<DateTime<Utc>>,
+}
+
+impl KeyState {
+    pub fn is_exhausted(&self, now: DateTime<Utc>) -> bool {
+        self.exhausted_until.is_some_and(|until| now < until)
+    }
+
+    /// State after a response with `status` that reported `remaining` requests left.
+    pub fn observe(&self, status: u16, remaining: Option<u32>, now: DateTime<Utc>) -> KeyState {
+        if status == 429 {
+            KeyState { remaining: Some(0), exhausted_until: Some(now + EXHAUSTION_COOLDOWN) }
+        } else {
+            KeyState { remaining: remaining.or(self.remaining), exhausted_until: None }
+        }
+    }
+
+    /// Whether `next` differs enough from `self` to be worth a KV write.
+    pub fn worth_saving(&self, next: &KeyState) -> bool {
+        let step = |state: &KeyState| state.remaining.map(|remaining| remaining / SAVE_GRANULARITY);
+        self.exhausted_until != next.exhausted_until || step(self) != step(next)
+    }
+}
+
+/// Indices of the keys to try, best first: usable keys with the most quota left
+/// (unknown counts as full), ties in configured order. Exhausted keys are only
+/// tried when nothing else is left, and then just the one that recovers first.
+pub fn order(states: &[KeyState], now: DateTime<Utc>) -> Vec<usize> {
+    let mut usable: Vec<usize> = (0..states.len()).filter(|&i| !states[i].is_exhausted(now)).collect();
+    usable.sort_by_key(|&i| Reverse(states[i].remaining.unwrap_or(u32::MAX)));
+
+    if usable.is_empty() {
+        return (0..states.len()).min_by_key(|&i| states[i].exhausted_until).into_iter().collect();
+    }
+    usable
+}
+
+/// Where key quota state lives between requests, keyed by key fingerprint.
+#[async_trait(?Send)]
+pub trait KeyStore {
+    async fn load(&self, fingerprint: &str) -> KeyState;
+    async fn save(&self, fingerprint: &str, state: &KeyState);
+}
+
+/// Quota state kept only for the lifetime of one client. Used where no KV
+/// namespace is available.
+#[derive(Default)]
+pub struct MemoryKeyStore {
+    states: RefCell<HashMap<String, KeyState>>,
+}
+
+#[async_trait(?Send)]
+impl KeyStore for MemoryKeyStore {
+    async fn load(&self, fingerprint: &str) -> KeyState {
+        self.states.borrow().get(fingerprint).cloned().unwrap_or_default()
+    }
+
+    async fn save(&self, fingerprint: &str, state: &KeyState) {
+        self.states.borrow_mut().insert(fingerprint.to_string(), state.clone());
+    }
+}
+
+/// Quota state shared by all isolates through KV.
+pub struct KvKeyStore {
+    kv: KvStore,
+}
+
+impl KvKeyStore {
+    /// Figures older than the upstream's rolling window are meaningless.
+    const TTL_SECONDS: u64 = 3600;
+
+    pub fn new(env: &Env) -> Option<Self> {
+        env.kv("RATE_LIMIT").ok().map(|kv| Self { kv })
+    }
+
+    fn key(fingerprint: &str) -> String {
+        format!("quota:{fingerprint}")
+    }
+}
+
+#[async_trait(?Send)]
+impl KeyStore for KvKeyStore {
+    async fn load(&self, fingerprint: &str) -> KeyState {
+        match self.kv.get(&Self::key(fingerprint)).json::<KeyState>().await {
+            Ok(state) => state.unwrap_or_default(),
+            Err(e) => {
+                console_error!("Failed to read quota for key {}: {}", fingerprint, e);
+                KeyState::default()
+            }
+        }
+    }
+
+    async fn save(&self, fingerprint: &str, state: &KeyState) {
+        let result = match serde_json::to_string(state) {
+            Ok(value) => match self.kv.put(&Self::key(fingerprint), value) {
+                Ok(put) => put.expiration_ttl(Self::TTL_SECONDS).execute().await,
+                Err(e) => Err(e),
+            },
+            Err(e) => {
+                console_error!("Failed to serialize key quota: {}", e);
+                return;
+            }
+        };
+
+        if let Err(e) = result {
+            console_error!("Failed to write quota for key {}: {}", fingerprint, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000, 0).unwrap() + Duration::minutes(minutes)
+    }
+
+    fn remaining(remaining: u32) -> KeyState {
+        KeyState { remaining: Some(remaining), exhausted_until: None }
+    }
+
+    #[test]
+    fn parses_comma_separated_and_numbered_keys() {
+        let values = vec!["KEY_A, KEY_B,".to_string(), "KEY_C".to_string(), "KEY_A".to_string()];
+
+        assert_eq!(parse_pool(values), vec!["KEY_A", "KEY_B", "KEY_C"]);
+    }
+
+    #[test]
+    fn prefers_keys_with_most_quota_left() {
+        let exhausted = KeyState { remaining: Some(0), exhausted_until: Some(at(30)) };
+        let states = [remaining(10), exhausted, KeyState::default(), remaining(900)];
+
+        assert_eq!(order(&states, at(0)), vec![2, 3, 0]);
+        assert_eq!(order(&states, at(30)), vec![2, 3, 0, 1]);
+    }
+
+    #[test]
+    fn falls_back_to_the_key_that_recovers_first() {
+        let states = [
+            KeyState { remaining: Some(0), exhausted_until: Some(at(50)) },
+            KeyState { remaining: Some(0), exhausted_until: Some(at(20)) },
+        ];
+
+        assert_eq!(order(&states, at(0)), vec![1]);
+        assert!(order(&[], at(0)).is_empty());
+    }
+
+    #[test]
+    fn tracks_quota_from_responses() {
+        let state = remaining(500);
+
+        assert_eq!(state.observe(200, Some(499), at(0)), remaining(499));
+        assert_eq!(state.observe(200, None, at(0)), remaining(500));
+        assert_eq!(
+            state.observe(429, None, at(0)),
+            KeyState { remaining: Some(0), exhausted_until: Some(at(60)) }
+        );
+    }
+
+    #[test]
+    fn saves_only_meaningful_changes() {
+        assert!(!remaining(499).worth_saving(&remaining(451)));
+        assert!(remaining(451).worth_saving(&remaining(449)));
+        assert!(KeyState::default().worth_saving(&remaining(999)));
+        assert!(remaining(10).worth_saving(&remaining(0).observe(429, None, at(0))));
+    }
+}
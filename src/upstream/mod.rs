@@ -0,0 +1,619 @@
+//! Upstream client shared by every proxied data source.
+//!
+//! Handlers never talk to `reqwest` directly. They describe *where* a request goes
+//! with a [`Source`] and hand the path and query to an [`UpstreamClient`], which
+//! builds the URL, injects the NASA API key where required, applies the timeout and
+//! maps failures to the gateway variants of [`NasaApiError`], tagged with the
+//! [`Source`] that failed. Transient failures are retried with backoff
+//! ([`retry`]), hosts that keep failing are short-circuited ([`breaker`]), and
+//! requests to keyed sources rotate through a pool of NASA API keys ([`keys`]).
+//!
+//! The network itself sits behind the [`Upstream`] trait so handler logic can be
+//! exercised against a fake transport without touching the network.
+
+pub mod breaker;
+pub mod keys;
+pub mod retry;
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use futures::future::{self, Either};
+use url::Url;
+use worker::{Delay, Env};
+
+use crate::error::{NasaApiError, Result};
+use crate::middleware::client::fingerprint;
+use crate::utils;
+use breaker::{Admission, BreakerConfig, BreakerState, BreakerStore, KvBreakerStore, MemoryBreakerStore};
+use keys::{KeyState, KeyStore, KvKeyStore, MemoryKeyStore};
+use retry::RetryPolicy;
+
+/// Default time allowed for a single upstream request.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// User agent sent with every upstream request.
+const USER_AGENT: &str = concat!("nasa-rs/", env!("CARGO_PKG_VERSION"));
+
+/// Upstream response headers that are kept on an [`UpstreamResponse`].
+const FORWARDED_HEADERS: &[&str] = &["content-type", "x-ratelimit-limit", "x-ratelimit-remaining"];
+
+/// Static configuration for one upstream data source.
+#[derive(Debug)]
+pub struct Source {
+    /// Human-readable label used in error messages.
+    pub label: &'static str,
+    /// Base URL that request paths are appended to.
+    pub base_url: &'static str,
+    /// Whether the NASA `api_key` query parameter is injected.
+    pub api_key: bool,
+}
+
+/// api.nasa.gov (APOD, NeoWs, DONKI, Earth, Mars Rover Photos, Tech Transfer).
+pub const NASA: Source = Source {
+    label: "NASA API",
+    base_url: "https://api.nasa.gov",
+    api_key: true,
+};
+
+/// EPIC, served from api.nasa.gov under its own prefix.
+pub const EPIC: Source = Source {
+    label: "NASA EPIC API",
+    base_url: "https://api.nasa.gov/EPIC/api",
+    api_key: true,
+};
+
+/// JPL Solar System Dynamics APIs.
+pub const SSD: Source = Source {
+    label: "JPL SSD API",
+    base_url: "https://ssd-api.jpl.nasa.gov",
+    api_key: false,
+};
+
+/// NASA Image and Video Library.
+pub const MEDIA: Source = Source {
+    label: "NASA Media API",
+    base_url: "https://images-api.nasa.gov",
+    api_key: false,
+};
+
+/// NASA Exoplanet Archive TAP service.
+pub const EXOPLANET_ARCHIVE: Source = Source {
+    label: "Exoplanet Archive",
+    base_url: "https://exoplanetarchive.ipac.caltech.edu/TAP",
+    api_key: false,
+};
+
+/// A raw upstream response, before any status handling.
+#[derive(Debug, Clone, Default)]
+pub struct UpstreamResponse {
+    pub status: u16,
+    /// Lower-cased names and values of the [`FORWARDED_HEADERS`] present on the response.
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl UpstreamResponse {
+    /// Returns the value of a forwarded header, matched case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    pub fn json(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+}
+
+/// Why a request never produced an upstream response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransportError {
+    /// No response within the allowed time.
+    Timeout(Duration),
+    /// Connection, TLS or protocol failure.
+    Network(String),
+}
+
+/// Transport used to reach upstream APIs.
+///
+/// Implementations return whatever the server answered, including error statuses;
+/// status handling happens in [`UpstreamClient`].
+#[async_trait(?Send)]
+pub trait Upstream {
+    async fn send(&self, url: &Url) -> std::result::Result<UpstreamResponse, TransportError>;
+
+    /// Waits between retries.
+    async fn sleep(&self, duration: Duration);
+}
+
+/// [`Upstream`] implementation backed by `reqwest` (the Workers `fetch` API on wasm).
+pub struct HttpUpstream {
+    timeout: Duration,
+}
+
+impl HttpUpstream {
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+}
+
+impl Default for HttpUpstream {
+    fn default() -> Self {
+        Self::new(DEFAULT_TIMEOUT)
+    }
+}
+
+#[async_trait(?Send)]
+impl Upstream for HttpUpstream {
+    async fn send(&self, url: &Url) -> std::result::Result<UpstreamResponse, TransportError> {
+        let request = Box::pin(async {
+            let response = reqwest::Client::new()
+                .get(url.as_str())
+                .header("User-Agent", USER_AGENT)
+                .send()
+                .await
+                .map_err(|e| TransportError::Network(e.to_string()))?;
+
+            let status = response.status().as_u16();
+            let headers = FORWARDED_HEADERS
+                .iter()
+                .filter_map(|name| {
+                    response
+                        .headers()
+                        .get(*name)
+                        .and_then(|v| v.to_str().ok())
+                        .map(|v| (name.to_string(), v.to_string()))
+                })
+                .collect();
+            let body = response
+                .bytes()
+                .await
+                .map_err(|e| TransportError::Network(e.to_string()))?
+                .to_vec();
+
+            Ok(UpstreamResponse { status, headers, body })
+        });
+
+        match future::select(request, Delay::from(self.timeout)).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => Err(TransportError::Timeout(self.timeout)),
+        }
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        Delay::from(duration).await;
+    }
+}
+
+/// Builds upstream URLs for a [`Source`] and turns raw responses into results.
+pub struct UpstreamClient<U = HttpUpstream> {
+    upstream: U,
+    api_keys: Vec<String>,
+    retry: RetryPolicy,
+    breaker: BreakerConfig,
+    breakers: Box<dyn BreakerStore>,
+    quotas: Box<dyn KeyStore>,
+}
+
+impl UpstreamClient<HttpUpstream> {
+    /// Creates a client using the real HTTP transport, the NASA API keys from
+    /// `env`, and circuit breakers and key quotas shared through KV.
+    ///
+    /// Missing keys only become an error once a keyed [`Source`] is requested.
+    pub fn from_env(env: &Env) -> Self {
+        let mut client = Self::new(HttpUpstream::default(), None).with_api_keys(utils::get_api_keys(env));
+        if let Some(store) = KvBreakerStore::new(env) {
+            client = client.with_breaker_store(store);
+        }
+        if let Some(store) = KvKeyStore::new(env) {
+            client = client.with_key_store(store);
+        }
+        client
+    }
+}
+
+impl<U: Upstream> UpstreamClient<U> {
+    /// Creates a client with the default retry policy, and breakers and key
+    /// quotas that only live as long as the client.
+    pub fn new(upstream: U, api_key: Option<String>) -> Self {
+        Self {
+            upstream,
+            api_keys: api_key.into_iter().collect(),
+            retry: RetryPolicy::default(),
+            breaker: BreakerConfig::default(),
+            breakers: Box::new(MemoryBreakerStore::default()),
+            quotas: Box::new(MemoryKeyStore::default()),
+        }
+    }
+
+    /// Replaces the NASA API keys with a pool to rotate through.
+    pub fn with_api_keys(mut self, api_keys: Vec<String>) -> Self {
+        self.api_keys = api_keys;
+        self
+    }
+
+    pub fn with_breaker_store(mut self, store: impl BreakerStore + 'static) -> Self {
+        self.breakers = Box::new(store);
+        self
+    }
+
+    pub fn with_key_store(mut self, store: impl KeyStore + 'static) -> Self {
+        self.quotas = Box::new(store);
+        self
+    }
+
+    /// Builds the full upstream URL for `path` on `source`.
+    ///
+    /// Client-supplied `api_key` parameters are always dropped; `api_key`, the
+    /// worker's own key, is appended when the source requires one.
+    pub fn url(&self, source: &Source, path: &str, query: &[(String, String)], api_key: Option<&str>) -> Result<Url> {
+        let mut url = Url::parse(&format!("{}{}", source.base_url, path))
+            .map_err(|e| NasaApiError::Internal(format!("Invalid upstream URL: {e}")))?;
+
+        let params: Vec<&(String, String)> = query.iter().filter(|(k, _)| k != "api_key").collect();
+        if !params.is_empty() || source.api_key {
+            let mut pairs = url.query_pairs_mut();
+            for (key, value) in params {
+                pairs.append_pair(key, value);
+            }
+            if let (true, Some(api_key)) = (source.api_key, api_key) {
+                pairs.append_pair("api_key", api_key);
+            }
+        }
+
+        Ok(url)
+    }
+
+    /// Performs a GET against `source`, failing on any non-success status.
+    ///
+    /// Fails fast while the circuit for the source's host is open. For keyed
+    /// sources, a 429 is retried with the next key in the pool.
+    pub async fn get(&self, source: &Source, path: &str, query: &[(String, String)]) -> Result<UpstreamResponse> {
+        let keys = self.key_order(source).await?;
+        let host = Url::parse(source.base_url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_else(|| source.base_url.to_string());
+
+        let state = self.breakers.load(&host).await;
+        match self.breaker.admit(&state, Utc::now()) {
+            Admission::Allow => {}
+            Admission::Probe => self.breakers.save(&host, &BreakerState::HalfOpen { since: Utc::now() }).await,
+            Admission::Reject { until } => {
+                return Err(NasaApiError::UpstreamUnavailable {
+                    upstream: source.label,
+                    detail: format!("circuit open after repeated failures, retrying after {}", until.to_rfc3339()),
+                })
+            }
+        }
+
+        let mut index = 0;
+        let (url, outcome) = loop {
+            let key = keys[index].as_ref();
+            let url = self.url(source, path, query, key.map(|(key, _)| *key))?;
+            let outcome = self.send_with_retries(&url).await;
+
+            if let (Some((key, state)), Ok(response)) = (key, &outcome) {
+                self.record_quota(key, state, response).await;
+                if response.status == 429 && index + 1 < keys.len() {
+                    index += 1;
+                    continue;
+                }
+            }
+            break (url, outcome);
+        };
+
+        // Only the upstream's own failures count against it, not rejected parameters
+        let healthy = matches!(&outcome, Ok(response) if response.status < 500);
+        if healthy {
+            if state != BreakerState::default() {
+                self.breakers.save(&host, &self.breaker.on_success()).await;
+            }
+        } else {
+            self.breakers.save(&host, &self.breaker.on_failure(&state, Utc::now())).await;
+        }
+
+        let response = outcome.map_err(|e| match e {
+            TransportError::Timeout(timeout) => NasaApiError::UpstreamTimeout {
+                upstream: source.label,
+                seconds: timeout.as_secs(),
+            },
+            TransportError::Network(detail) => NasaApiError::UpstreamUnavailable { upstream: source.label, detail },
+        })?;
+
+        match response.status {
+            200..=299 => Ok(response),
+            status => Err(status_error(source, &url, status, &response)),
+        }
+    }
+
+    /// Keys to try for `source`, best first, with their last known quota; a
+    /// single `None` for sources that take no key.
+    async fn key_order(&self, source: &Source) -> Result<Vec<Option<(&str, KeyState)>>> {
+        if !source.api_key {
+            return Ok(vec![None]);
+        }
+        if self.api_keys.is_empty() {
+            return Err(NasaApiError::Internal("NASA_API_KEY not found in secrets or environment variables".to_string()));
+        }
+
+        let mut states = Vec::with_capacity(self.api_keys.len());
+        for key in &self.api_keys {
+            states.push(self.quotas.load(&fingerprint(key)).await);
+        }
+        Ok(keys::order(&states, Utc::now())
+            .into_iter()
+            .map(|i| Some((self.api_keys[i].as_str(), states[i].clone())))
+            .collect())
+    }
+
+    /// Updates a key's quota from the upstream's answer.
+    async fn record_quota(&self, key: &str, state: &KeyState, response: &UpstreamResponse) {
+        let remaining = response.header("x-ratelimit-remaining").and_then(|v| v.trim().parse().ok());
+        let next = state.observe(response.status, remaining, Utc::now());
+        if state.worth_saving(&next) {
+            self.quotas.save(&fingerprint(key), &next).await;
+        }
+    }
+
+    async fn send_with_retries(&self, url: &Url) -> std::result::Result<UpstreamResponse, TransportError> {
+        let mut attempt = 1;
+        loop {
+            let outcome = self.upstream.send(url).await;
+            if attempt >= self.retry.max_attempts || !RetryPolicy::should_retry(&outcome) {
+                return outcome;
+            }
+            self.upstream.sleep(self.retry.backoff(attempt - 1, retry::jitter())).await;
+            attempt += 1;
+        }
+    }
+}
+
+/// Maps a non-success upstream status to an error.
+///
+/// A 404 (unknown asteroid, `nasa_id`, ...) is passed on as [`NasaApiError::NotFound`].
+/// Other client errors mean the upstream refused our parameters, except 429 (our
+/// key is out of quota) and 401/403 (our key is invalid), which are not the
+/// caller's fault. Everything else means the upstream is failing.
+fn status_error(source: &Source, url: &Url, status: u16, response: &UpstreamResponse) -> NasaApiError {
+    let detail = format!("{status} - {}", response.text());
+    match status {
+        404 => NasaApiError::NotFound(format!("{} has no resource at {}", source.label, url.path())),
+        429 => NasaApiError::UpstreamQuotaExhausted { upstream: source.label, detail },
+        401 | 403 => NasaApiError::UpstreamUnavailable { upstream: source.label, detail },
+        400..=499 => NasaApiError::UpstreamRejected { upstream: source.label, detail },
+        _ => NasaApiError::UpstreamUnavailable { upstream: source.label, detail },
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+
+    /// In-memory [`Upstream`] that records requested URLs and replays canned
+    /// responses: queued ones first, then `response` forever. Never sleeps.
+    #[derive(Default)]
+    pub(crate) struct FakeUpstream {
+        pub requests: RefCell<Vec<String>>,
+        pub queued: RefCell<VecDeque<std::result::Result<UpstreamResponse, TransportError>>>,
+        pub response: UpstreamResponse,
+        pub sleeps: RefCell<Vec<Duration>>,
+    }
+
+    impl FakeUpstream {
+        pub fn json(status: u16, body: &str) -> Self {
+            Self {
+                response: UpstreamResponse {
+                    status,
+                    headers: vec![("content-type".to_string(), "application/json".to_string())],
+                    body: body.as_bytes().to_vec(),
+                },
+                ..Self::default()
+            }
+        }
+
+        /// Answers with `queued` before falling back to `response`.
+        pub fn then(self, queued: Vec<std::result::Result<UpstreamResponse, TransportError>>) -> Self {
+            Self { queued: RefCell::new(queued.into()), ..self }
+        }
+    }
+
+    #[async_trait(?Send)]
+    impl Upstream for &FakeUpstream {
+        async fn send(&self, url: &Url) -> std::result::Result<UpstreamResponse, TransportError> {
+            self.requests.borrow_mut().push(url.to_string());
+            self.queued.borrow_mut().pop_front().unwrap_or_else(|| Ok(self.response.clone()))
+        }
+
+        async fn sleep(&self, duration: Duration) {
+            self.sleeps.borrow_mut().push(duration);
+        }
+    }
+
+    fn status(status: u16) -> std::result::Result<UpstreamResponse, TransportError> {
+        Ok(UpstreamResponse { status, ..Default::default() })
+    }
+
+    fn params(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn injects_api_key_only_for_keyed_sources() {
+        let fake = FakeUpstream::json(200, "{}");
+        let client = UpstreamClient::new(&fake, Some("SECRET".to_string()));
+
+        block_on(client.get(&NASA, "/planetary/apod", &params(&[("date", "2024-01-01")]))).unwrap();
+        block_on(client.get(&SSD, "/cad.api", &params(&[("des", "433")]))).unwrap();
+
+        let requests = fake.requests.borrow();
+        assert_eq!(requests[0], "https://api.nasa.gov/planetary/apod?date=2024-01-01&api_key=SECRET");
+        assert_eq!(requests[1], "https://ssd-api.jpl.nasa.gov/cad.api?des=433");
+    }
+
+    #[test]
+    fn strips_client_supplied_api_key_and_encodes_values() {
+        let fake = FakeUpstream::json(200, "{}");
+        let client = UpstreamClient::new(&fake, None);
+
+        block_on(client.get(&MEDIA, "/search", &params(&[("api_key", "DEMO_KEY"), ("q", "apollo 11")]))).unwrap();
+
+        assert_eq!(fake.requests.borrow()[0], "https://images-api.nasa.gov/search?q=apollo+11");
+    }
+
+    #[test]
+    fn missing_api_key_fails_before_sending() {
+        let fake = FakeUpstream::json(200, "{}");
+        let client = UpstreamClient::new(&fake, None);
+
+        let err = block_on(client.get(&NASA, "/planetary/apod", &[])).unwrap_err();
+
+        assert!(matches!(err, NasaApiError::Internal(_)));
+        assert!(fake.requests.borrow().is_empty());
+    }
+
+    #[test]
+    fn maps_error_status_with_source_label() {
+        let fake = FakeUpstream::json(503, "Service Unavailable");
+        let client = UpstreamClient::new(&fake, None);
+
+        let err = block_on(client.get(&SSD, "/sentry.api", &[])).unwrap_err();
+
+        assert_eq!(err.to_string(), "JPL SSD API is unavailable: 503 - Service Unavailable");
+        assert_eq!(err.status_code(), 502);
+        assert_eq!(err.upstream(), Some("JPL SSD API"));
+    }
+
+    #[test]
+    fn distinguishes_rejected_requests_from_exhausted_quota() {
+        let rejected = FakeUpstream::json(400, "Bad Request");
+        let err = block_on(UpstreamClient::new(&rejected, None).get(&EXOPLANET_ARCHIVE, "/sync", &[])).unwrap_err();
+        assert!(matches!(err, NasaApiError::UpstreamRejected { upstream: "Exoplanet Archive", .. }));
+
+        let over_quota = FakeUpstream::json(429, "OVER_RATE_LIMIT");
+        let err = block_on(UpstreamClient::new(&over_quota, Some("KEY".to_string())).get(&EPIC, "/natural", &[])).unwrap_err();
+        assert_eq!(err.status_code(), 503);
+        assert_eq!(err.upstream(), Some("NASA EPIC API"));
+    }
+
+    #[test]
+    fn maps_upstream_404_to_not_found() {
+        let fake = FakeUpstream::json(404, r#"{"error": "not found"}"#);
+        let client = UpstreamClient::new(&fake, None);
+
+        let err = block_on(client.get(&MEDIA, "/asset/missing", &[])).unwrap_err();
+
+        assert_eq!(err.status_code(), 404);
+        assert_eq!(err.to_string(), "Not found: NASA Media API has no resource at /asset/missing");
+    }
+
+    #[test]
+    fn retries_transient_failures_with_backoff() {
+        let fake = FakeUpstream::json(200, "{}").then(vec![status(502), Err(TransportError::Network("reset".to_string()))]);
+        let client = UpstreamClient::new(&fake, None);
+
+        block_on(client.get(&SSD, "/cad.api", &[])).unwrap();
+
+        assert_eq!(fake.requests.borrow().len(), 3);
+        let sleeps = fake.sleeps.borrow();
+        assert_eq!(sleeps.len(), 2);
+        assert!(sleeps[0] <= Duration::from_millis(250) && sleeps[1] <= Duration::from_millis(500));
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts_and_skips_non_transient_errors() {
+        let failing = FakeUpstream::json(503, "down");
+        block_on(UpstreamClient::new(&failing, None).get(&SSD, "/cad.api", &[])).unwrap_err();
+        assert_eq!(failing.requests.borrow().len(), 3);
+
+        let timing_out = FakeUpstream::json(200, "{}").then(vec![Err(TransportError::Timeout(DEFAULT_TIMEOUT))]);
+        let err = block_on(UpstreamClient::new(&timing_out, None).get(&SSD, "/cad.api", &[])).unwrap_err();
+        assert_eq!(err.status_code(), 504);
+        assert_eq!(timing_out.requests.borrow().len(), 1);
+    }
+
+    #[test]
+    fn open_circuit_fails_fast_until_a_probe_succeeds() {
+        let fake = FakeUpstream::json(503, "down");
+        let client = UpstreamClient::new(&fake, None);
+
+        for _ in 0..BreakerConfig::default().failure_threshold {
+            block_on(client.get(&MEDIA, "/search", &[])).unwrap_err();
+        }
+        let sent = fake.requests.borrow().len();
+        let err = block_on(client.get(&MEDIA, "/search", &[])).unwrap_err();
+
+        assert!(err.to_string().contains("circuit open"), "{err}");
+        assert_eq!(fake.requests.borrow().len(), sent);
+
+        // Once the cooldown has passed, one probe goes through and closes the circuit
+        let expired = BreakerState::Open { since: Utc::now() - BreakerConfig::default().cooldown };
+        block_on(client.breakers.save("images-api.nasa.gov", &expired));
+        let healthy = FakeUpstream::json(200, "{}");
+        let client = UpstreamClient { upstream: &healthy, ..client };
+
+        block_on(client.get(&MEDIA, "/search", &[])).unwrap();
+        assert_eq!(block_on(client.breakers.load("images-api.nasa.gov")), BreakerState::default());
+    }
+
+    #[test]
+    fn rotates_to_the_next_key_when_one_is_exhausted() {
+        let fake = FakeUpstream::json(200, "{}").then(vec![status(429)]);
+        let client = UpstreamClient::new(&fake, None).with_api_keys(vec!["A".to_string(), "B".to_string()]);
+
+        block_on(client.get(&NASA, "/planetary/apod", &[])).unwrap();
+        block_on(client.get(&NASA, "/planetary/apod", &[])).unwrap();
+
+        let requests = fake.requests.borrow();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].ends_with("api_key=A") && requests[1].ends_with("api_key=B"));
+        // A stays set aside until its quota window has passed
+        assert!(requests[2].ends_with("api_key=B"));
+        assert!(block_on(client.quotas.load(&fingerprint("A"))).is_exhausted(Utc::now()));
+    }
+
+    #[test]
+    fn prefers_the_key_with_most_quota_left() {
+        let mut fake = FakeUpstream::json(200, "{}");
+        fake.response.headers.push(("x-ratelimit-remaining".to_string(), "40".to_string()));
+        let client = UpstreamClient::new(&fake, None).with_api_keys(vec!["A".to_string(), "B".to_string()]);
+        block_on(client.quotas.save(&fingerprint("A"), &KeyState { remaining: Some(10), exhausted_until: None }));
+        block_on(client.quotas.save(&fingerprint("B"), &KeyState { remaining: Some(900), exhausted_until: None }));
+
+        block_on(client.get(&EPIC, "/natural", &[])).unwrap();
+
+        assert!(fake.requests.borrow()[0].ends_with("api_key=B"));
+        assert_eq!(block_on(client.quotas.load(&fingerprint("B"))).remaining, Some(40));
+    }
+
+    #[test]
+    fn reports_exhausted_quota_once_every_key_is_spent() {
+        let fake = FakeUpstream::json(429, "OVER_RATE_LIMIT");
+        let client = UpstreamClient::new(&fake, None).with_api_keys(vec!["A".to_string(), "B".to_string()]);
+
+        let err = block_on(client.get(&NASA, "/planetary/apod", &[])).unwrap_err();
+
+        assert!(matches!(err, NasaApiError::UpstreamQuotaExhausted { .. }));
+        assert_eq!(fake.requests.borrow().len(), 2);
+    }
+
+    #[test]
+    fn parses_json_body() {
+        let fake = FakeUpstream::json(200, r#"{"count": "1"}"#);
+        let client = UpstreamClient::new(&fake, None);
+
+        let value = block_on(client.get(&SSD, "/cad.api", &[])).unwrap().json().unwrap();
+
+        assert_eq!(value["count"], "1");
+    }
+}
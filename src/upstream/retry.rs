@@ -0,0 +1,97 @@
+//! Bounded retries with jittered exponential backoff for upstream GETs.
+
+use std::time::Duration;
+
+use super::{TransportError, UpstreamResponse};
+
+/// How often and how patiently a failed upstream request is retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first one.
+    pub max_attempts: u32,
+    /// Backoff ceiling before the first retry; doubled for every further retry.
+    pub base_delay: Duration,
+    /// Upper bound for any single backoff.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(2),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Backoff before retry number `retry` (starting at 0), using "full jitter":
+    /// a uniformly random delay between zero and the exponential ceiling.
+    /// `jitter` is a random fraction in `[0, 1)`.
+    pub fn backoff(&self, retry: u32, jitter: f64) -> Duration {
+        let ceiling = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+        ceiling.mul_f64(jitter.clamp(0.0, 1.0))
+    }
+
+    /// Whether an attempt that ended like this is worth repeating.
+    ///
+    /// Only connection failures and gateway-style statuses are retried: timeouts
+    /// would multiply the caller's wait, and other statuses won't change on a
+    /// second try.
+    pub fn should_retry(outcome: &std::result::Result<UpstreamResponse, TransportError>) -> bool {
+        match outcome {
+            Ok(response) => matches!(response.status, 500 | 502 | 503 | 504),
+            Err(TransportError::Network(_)) => true,
+            Err(TransportError::Timeout(_)) => false,
+        }
+    }
+}
+
+/// Random fraction in `[0, 1)` used to jitter backoffs.
+pub fn jitter() -> f64 {
+    let mut bytes = [0u8; 4];
+    match getrandom::getrandom(&mut bytes) {
+        Ok(()) => f64::from(u32::from_le_bytes(bytes)) / (f64::from(u32::MAX) + 1.0),
+        Err(_) => 0.5,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_grows_exponentially_up_to_the_cap() {
+        let policy = RetryPolicy::default();
+
+        assert_eq!(policy.backoff(0, 1.0), Duration::from_millis(250));
+        assert_eq!(policy.backoff(1, 1.0), Duration::from_millis(500));
+        assert_eq!(policy.backoff(5, 1.0), Duration::from_secs(2));
+        assert_eq!(policy.backoff(40, 1.0), Duration::from_secs(2));
+        assert_eq!(policy.backoff(1, 0.5), Duration::from_millis(250));
+        assert_eq!(policy.backoff(1, 0.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn retries_only_transient_failures() {
+        let status = |status| Ok(UpstreamResponse { status, ..Default::default() });
+
+        assert!(RetryPolicy::should_retry(&status(502)));
+        assert!(RetryPolicy::should_retry(&Err(TransportError::Network("reset".to_string()))));
+        assert!(!RetryPolicy::should_retry(&status(200)));
+        assert!(!RetryPolicy::should_retry(&status(404)));
+        assert!(!RetryPolicy::should_retry(&status(429)));
+        assert!(!RetryPolicy::should_retry(&Err(TransportError::Timeout(Duration::from_secs(15)))));
+    }
+
+    #[test]
+    fn jitter_is_a_fraction() {
+        for _ in 0..100 {
+            assert!((0.0..1.0).contains(&jitter()));
+        }
+    }
+}
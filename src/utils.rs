@@ -17,19 +17,25 @@ pub fn log_request(req: &Request) {
     );
 }
 
-pub fn get_api_key(env: &worker::Env) -> worker::Result<String> {
-    // First try to get from secret
-    if let Ok(secret) = env.secret("NASA_API_KEY") {
-        return Ok(secret.to_string());
-    }
-    
-    // Fall back to environment variable
-    if let Ok(api_key) = env.var("NASA_API_KEY") {
-        return Ok(api_key.to_string());
+/// NASA API keys: `NASA_API_KEY`, which may hold several comma-separated keys,
+/// followed by `NASA_API_KEY_1`, `NASA_API_KEY_2`, ... up to the first one that
+/// is missing. Each is read from secrets first, then from environment variables.
+pub fn get_api_keys(env: &worker::Env) -> Vec<String> {
+    let lookup = |name: &str| {
+        env.secret(name)
+            .map(|secret| secret.to_string())
+            .or_else(|_| env.var(name).map(|var| var.to_string()))
+            .ok()
+    };
+
+    let mut values: Vec<String> = lookup("NASA_API_KEY").into_iter().collect();
+    for n in 1.. {
+        match lookup(&format!("NASA_API_KEY_{n}")) {
+            Some(value) => values.push(value),
+            None => break,
+        }
     }
-    
-    // Return error if no API key is found
-    Err(worker::Error::RustError("NASA_API_KEY not found in secrets or environment variables".to_string()))
+    crate::upstream::keys::parse_pool(values)
 }
 
 pub fn parse_query_params(req: &Request) -> worker::Result<Vec<(String, String)>> {
@@ -40,7 +46,14 @@ pub fn parse_query_params(req: &Request) -> worker::Result<Vec<(String, String)>
         .collect())
 }
 
-#[allow(dead_code)]
+/// The first value of the query parameter `name`, if present.
+pub fn query_param(req: &Request, name: &str) -> worker::Result<Option<String>> {
+    Ok(parse_query_params(req)?
+        .into_iter()
+        .find(|(k, _)| k == name)
+        .map(|(_, v)| v))
+}
+
 pub fn get_client_ip(req: &Request) -> String {
     req.headers()
         .get("CF-Connecting-IP")
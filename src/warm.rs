@@ -0,0 +1,140 @@
+//! Scheduled cache pre-warming.
+//!
+//! On every cron trigger the worker fetches the requests listed in the
+//! `CACHE_WARM_LIST` var, so popular entries are already cached when the first
+//! user asks for them. The list holds one request path per line, e.g.
+//! `/api/donki/flr?startDate={today-7}&endDate={today}`; blank lines and lines
+//! starting with `#` are ignored. `{today}`, `{today-N}` and `{today+N}` expand
+//! to UTC dates relative to the run. Entries that are still fresh are left alone.
+
+use chrono::{Duration, NaiveDate, Utc};
+use futures::future::join_all;
+use worker::{console_error, console_log, Env};
+
+use crate::error::{NasaApiError, Result};
+use crate::handlers::proxy;
+use crate::registry::{self, Endpoint, ProxyRequest};
+
+/// The warm list's request paths with their date placeholders expanded.
+pub fn parse_list(list: &str, today: NaiveDate) -> Vec<String> {
+    list.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| expand_dates(line, today))
+        .collect()
+}
+
+/// Replaces `{today}`, `{today-N}` and `{today+N}` with `YYYY-MM-DD` dates.
+/// Malformed placeholders are left in place for validation to reject.
+fn expand_dates(entry: &str, today: NaiveDate) -> String {
+    let mut expanded = String::new();
+    let mut rest = entry;
+
+    while let Some(start) = rest.find("{today") {
+        expanded.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            break;
+        };
+        let placeholder = &rest[start..=end];
+        let offset = match &placeholder["{today".len()..placeholder.len() - 1] {
+            "" => Some(0),
+            offset => offset.strip_prefix('+').unwrap_or(offset).parse::<i64>().ok(),
+        };
+        match offset {
+            Some(days) => expanded.push_str(&(today + Duration::days(days)).format("%Y-%m-%d").to_string()),
+            None => expanded.push_str(placeholder),
+        }
+        rest = &rest[end + 1..];
+    }
+
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Resolves one warm-list entry against the registry, the same way the router
+/// and the proxy pipeline resolve an incoming request. `limit` gives the
+/// configured span limit of an endpoint (see [`proxy::span_limit`]).
+pub fn resolve(entry: &str, limit: impl Fn(&Endpoint) -> Option<i64>) -> Result<(&'static Endpoint, ProxyRequest)> {
+    let url = url::Url::parse("https://warm.invalid")
+        .and_then(|base| base.join(entry))
+        .map_err(|e| NasaApiError::BadRequest(format!("Invalid warm list entry {entry}: {e}")))?;
+    let endpoint = registry::resolve(url.path())
+        .ok_or_else(|| NasaApiError::NotFound(format!("No proxied endpoint for {}", url.path())))?;
+    let path = endpoint.path_params(url.path()).unwrap_or_default();
+    let query = url.query_pairs().map(|(k, v)| (k.into_owned(), v.into_owned())).collect();
+
+    let request = ProxyRequest::new(endpoint, path, query);
+    endpoint.check(&request, limit(endpoint))?;
+    Ok((endpoint, request))
+}
+
+/// Warms every entry of the configured list concurrently, logging the outcome.
+pub async fn run(env: &Env) {
+    let list = env.var("CACHE_WARM_LIST").map(|v| v.to_string()).unwrap_or_default();
+    let entries = parse_list(&list, Utc::now().date_naive());
+    if entries.is_empty() {
+        console_log!("CACHE_WARM_LIST is empty; nothing to warm");
+        return;
+    }
+
+    let results = join_all(entries.iter().map(|entry| async move {
+        let (endpoint, request) = resolve(entry, |endpoint| proxy::span_limit(endpoint, env))?;
+        proxy::warm(endpoint, env, request).await
+    }))
+    .await;
+
+    let mut warmed = 0;
+    for (entry, result) in entries.iter().zip(results) {
+        match result {
+            Ok(status) => {
+                warmed += 1;
+                console_log!("Warmed {} ({})", entry, status);
+            }
+            Err(e) => console_error!("Failed to warm {}: {}", entry, e),
+        }
+    }
+    console_log!("Warmed {} of {} cache entries", warmed, entries.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, 1).unwrap()
+    }
+
+    #[test]
+    fn parses_list_and_expands_relative_dates() {
+        let list = "
+            # space weather
+            /api/donki/flr?startDate={today-7}&endDate={today}
+
+            /api/neo/feed?start_date={today+1}&end_date={today+7}
+            /api/apod?date={yesterday}
+        ";
+
+        assert_eq!(
+            parse_list(list, today()),
+            vec![
+                "/api/donki/flr?startDate=2024-02-23&endDate=2024-03-01",
+                "/api/neo/feed?start_date=2024-03-02&end_date=2024-03-08",
+                "/api/apod?date={yesterday}",
+            ]
+        );
+    }
+
+    #[test]
+    fn resolves_entries_like_requests() {
+        let (endpoint, request) = resolve("/api/mars-photos/Curiosity/latest", |_| None).unwrap();
+        assert_eq!(endpoint.route, "/api/mars-photos/:rover/latest");
+        assert_eq!(endpoint.cache_key(&request), "mars-photos/curiosity/latest:");
+
+        let (endpoint, request) = resolve("/api/donki/cme?startDate=2024-2-23", |_| None).unwrap();
+        assert_eq!(endpoint.cache_key(&request), "donki/cme:startDate=2024-02-23");
+        assert_eq!(resolve("/api/donki/cme?utm_source=cron", |_| None).unwrap_err().status_code(), 400);
+
+        assert_eq!(resolve("/api/nope", |_| None).unwrap_err().status_code(), 404);
+        assert_eq!(resolve("/api/mars-photos/zhurong/latest", |_| None).unwrap_err().status_code(), 400);
+    }
+}